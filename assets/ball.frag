@@ -13,6 +13,14 @@ uniform float ambient_occlusion_strength;
 uniform float ambient_light;
 uniform float specular_focus;
 uniform float specular_strength;
+// Where the light comes from in radians. 0 is straight above and it goes clockwise.
+uniform float light_angle;
+// How far in front of the ball the light is. Lower makes the highlight move closer to the edge.
+uniform float light_height;
+// How many pixels the edge of the ball fades out over. 0 turns it off.
+uniform float smooth_edge;
+// 1 when the ball gets multiplied with what's behind it. See-through parts go towards white so they don't darken it.
+uniform float multiply;
 
 uniform sampler2D Texture;
 
@@ -37,13 +45,18 @@ void main() {
 
     float antialiasing_alpha_mul = 1.0 - max((center_length + delta_uv * 2.0) - 1.0, 0.0) / (delta_uv * 2.0);
 
+    // Hard edges in the texture look jagged when the ball rotates, so the whole rim gets faded out smoothly.
+    if (smooth_edge > 0.0) {
+        antialiasing_alpha_mul = min(antialiasing_alpha_mul, 1.0 - smoothstep(1.0 - delta_uv * smooth_edge, 1.0, center_length));
+    }
+
     vec2 rotated_uv = rotate(minus_one_to_one_uv, rotation);
 
     float z = sqrt(1.0 - minus_one_to_one_uv.x * minus_one_to_one_uv.x - minus_one_to_one_uv.y * minus_one_to_one_uv.y);
     
     vec3 normal = vec3(rotated_uv, z);
     
-    vec3 light_dir = vec3(0.6, 0.8, -1.2);
+    vec3 light_dir = vec3(-sin(light_angle), cos(light_angle), -light_height);
     vec3 normalized_light_dir = normalize(light_dir);
 
     vec4 cardboard_shadow_color = vec4(40, 20, 8, 255) / 255.;
@@ -85,5 +98,7 @@ void main() {
 
     final_color.a *= antialiasing_alpha_mul;
 
+    final_color.rgb = mix(final_color.rgb, mix(vec3(1.0), final_color.rgb, final_color.a), multiply);
+
     gl_FragColor = final_color;
 }
\ No newline at end of file
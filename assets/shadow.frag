@@ -4,6 +4,7 @@ varying lowp vec4 color;
 
 uniform float in_shadow;
 uniform float shadow_strength;
+uniform vec3 shadow_color;
 
 uniform sampler2D Texture;
 
@@ -15,5 +16,5 @@ void main() {
     vec2 minus_one_to_one_uv = uv * 2.0 - 1.0;
 
     float darkness = 1.0 - length(minus_one_to_one_uv) - in_shadow / 3;
-    gl_FragColor = vec4(0.,0., 0., easing_function(clamp(darkness, 0.0, 1.0)) * shadow_strength / (2. + in_shadow * 2.));
+    gl_FragColor = vec4(shadow_color, easing_function(clamp(darkness, 0.0, 1.0)) * shadow_strength / (2. + in_shadow * 2.));
 }
\ No newline at end of file
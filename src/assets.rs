@@ -6,12 +6,20 @@ use macroquad::{
     texture::Texture2D,
 };
 use miniquad::{BlendFactor, BlendState, BlendValue, Equation};
+use nanoserde::DeJson;
 
-use crate::error_log::ErrorLogs;
+use crate::{
+    error_log::ErrorLogs,
+    lang::Translations,
+    paths::get_game_path,
+    settings::{BallBlendMode, MatchMode, Settings, TextureFilter},
+};
 
 pub struct GameAssets {
     pub missing_texture: Texture2D,
     pub box_background_texture: Texture2D,
+    /// False when the box background fell back to the missing texture, so a solid color can be drawn instead.
+    pub box_background_loaded: bool,
     pub box_side_texture: Texture2D,
     pub menu_background: Texture2D,
     pub menu_button: Texture2D,
@@ -24,16 +32,148 @@ pub struct GameAssets {
     pub esc_normal: Texture2D,
     pub esc_hold: Texture2D,
     pub slash: Texture2D,
+    /// Drawn on top of the ball. Packs and the assets folder don't need to have it.
+    pub glass_overlay: Option<Texture2D>,
     pub ball_material: Material,
     pub shadow_material: Material,
-    pub font: Option<Font>,
+    pub glass_material: Material,
+    pub font: Font,
+    pub translations: Translations,
+    pub pack_settings: PackSettings,
+}
+
+/// Suggested visual settings that come with an asset pack, read from `pack_settings.json` in the pack.
+#[derive(Debug, DeJson, Default)]
+pub struct PackSettings {
+    ambient_occlusion_focus: Option<f32>,
+    ambient_occlusion_strength: Option<f32>,
+    specular_focus: Option<f32>,
+    specular_strength: Option<f32>,
+    ambient_light: Option<f32>,
+    shadow_size: Option<f32>,
+    shadow_distance_strength: Option<f32>,
+    shadow_strength: Option<f32>,
+    shadow_color: Option<[f32; 3]>,
+    light_angle: Option<f32>,
+    light_height: Option<f32>,
 }
 
+impl PackSettings {
+    /// Loads the settings from the pack. Packs without the file, and having no pack, get the default, which changes nothing.
+    pub fn load(pack_path: Option<PathBuf>, error_logs: &mut ErrorLogs) -> Self {
+        let Some(pack_path) = pack_path else {
+            return Self::default();
+        };
+        let path = pack_path.join("pack_settings.json");
+
+        if !path.is_file() {
+            return Self::default();
+        }
+
+        let string = match fs::read_to_string(&path) {
+            Ok(string) => string,
+            Err(err) => {
+                error_logs.display_error(format!(
+                    "Failed to read pack settings from \"{}\": {err}",
+                    path.to_string_lossy()
+                ));
+                return Self::default();
+            }
+        };
+
+        match Self::deserialize_json(&string) {
+            Ok(pack_settings) => return pack_settings,
+            Err(err) => {
+                error_logs.display_error(format!(
+                    "Failed to parse pack settings from \"{}\": {err}",
+                    path.to_string_lossy()
+                ));
+                return Self::default();
+            }
+        }
+    }
+
+    /// Applies the suggestions from the pack to the settings.
+    ///
+    /// Settings the user has changed from the default are left alone.
+    pub fn apply_to(&self, settings: &mut Settings) {
+        fn suggest<T: PartialEq + Copy>(value: &mut T, default: T, suggestion: Option<T>) {
+            if let Some(suggestion) = suggestion {
+                if *value == default {
+                    *value = suggestion;
+                }
+            }
+        }
+
+        let default_settings = Settings::default();
+
+        suggest(
+            &mut settings.ambient_occlusion_focus,
+            default_settings.ambient_occlusion_focus,
+            self.ambient_occlusion_focus,
+        );
+        suggest(
+            &mut settings.ambient_occlusion_strength,
+            default_settings.ambient_occlusion_strength,
+            self.ambient_occlusion_strength,
+        );
+        suggest(
+            &mut settings.specular_focus,
+            default_settings.specular_focus,
+            self.specular_focus,
+        );
+        suggest(
+            &mut settings.specular_strength,
+            default_settings.specular_strength,
+            self.specular_strength,
+        );
+        suggest(
+            &mut settings.ambient_light,
+            default_settings.ambient_light,
+            self.ambient_light,
+        );
+        suggest(
+            &mut settings.shadow_size,
+            default_settings.shadow_size,
+            self.shadow_size,
+        );
+        suggest(
+            &mut settings.shadow_distance_strength,
+            default_settings.shadow_distance_strength,
+            self.shadow_distance_strength,
+        );
+        suggest(
+            &mut settings.shadow_strength,
+            default_settings.shadow_strength,
+            self.shadow_strength,
+        );
+        suggest(
+            &mut settings.shadow_color,
+            default_settings.shadow_color,
+            self.shadow_color,
+        );
+        suggest(
+            &mut settings.light_angle,
+            default_settings.light_angle,
+            self.light_angle,
+        );
+        suggest(
+            &mut settings.light_height,
+            default_settings.light_height,
+            self.light_height,
+        );
+    }
+}
+
+/// Loads a texture from the pack, or the assets folder if the pack doesn't have it.
+///
+/// The filter is only set on textures that loaded, so the missing texture stays sharp.
 pub fn load_texture(
     asset_name: &str,
     mut assets_path: PathBuf,
     pack_path: Option<PathBuf>,
     missing_texture: &Texture2D,
+    filter: FilterMode,
     error_logs: &mut ErrorLogs,
 ) -> Texture2D {
     if let Some(mut pack_path) = pack_path {
@@ -52,25 +192,37 @@ pub fn load_texture(
                 }
             }
         } {
-            return Texture2D::from_file_with_format(&bytes, None).unwrap_or_else(|err| {
-                error_logs.display_error(format!(
-                    "Failed to read texture data from \"{}\": {err}",
-                    pack_path.to_string_lossy()
-                ));
-                missing_texture.clone()
-            });
+            return match Texture2D::from_file_with_format(&bytes, None) {
+                Ok(texture) => {
+                    texture.set_filter(filter);
+                    texture
+                }
+                Err(err) => {
+                    error_logs.display_error(format!(
+                        "Failed to read texture data from \"{}\": {err}",
+                        pack_path.to_string_lossy()
+                    ));
+                    missing_texture.clone()
+                }
+            };
         }
     }
     assets_path.push(asset_name);
     match fs::read(&assets_path) {
         Ok(bytes) => {
-            return Texture2D::from_file_with_format(&bytes, None).unwrap_or_else(|err| {
-                error_logs.display_error(format!(
-                    "Failed to read texture data from \"{}\": {err}",
-                    assets_path.to_string_lossy()
-                ));
-                missing_texture.clone()
-            });
+            return match Texture2D::from_file_with_format(&bytes, None) {
+                Ok(texture) => {
+                    texture.set_filter(filter);
+                    texture
+                }
+                Err(err) => {
+                    error_logs.display_error(format!(
+                        "Failed to read texture data from \"{}\": {err}",
+                        assets_path.to_string_lossy()
+                    ));
+                    missing_texture.clone()
+                }
+            };
         }
         Err(err) => {
             error_logs.display_error(format!(
@@ -82,6 +234,48 @@ pub fn load_texture(
     }
 }
 
+/// Loads a texture that doesn't have to exist. Returns `None` if it's not in the pack or the assets folder.
+pub fn load_optional_texture(
+    asset_name: &str,
+    assets_path: PathBuf,
+    pack_path: Option<PathBuf>,
+    error_logs: &mut ErrorLogs,
+) -> Option<Texture2D> {
+    let paths = pack_path
+        .into_iter()
+        .chain([assets_path])
+        .map(|path| path.join(asset_name));
+
+    for path in paths {
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                if err.kind() != ErrorKind::NotFound {
+                    error_logs.display_error(format!(
+                        "Failed to read texture bytes from \"{}\": {err}",
+                        path.to_string_lossy()
+                    ));
+                    return None;
+                }
+                continue;
+            }
+        };
+
+        match Texture2D::from_file_with_format(&bytes, None) {
+            Ok(texture) => return Some(texture),
+            Err(err) => {
+                error_logs.display_error(format!(
+                    "Failed to read texture data from \"{}\": {err}",
+                    path.to_string_lossy()
+                ));
+                return None;
+            }
+        }
+    }
+
+    return None;
+}
+
 pub fn load_assets_string(
     asset_name: &str,
     mut assets_path: PathBuf,
@@ -121,7 +315,30 @@ pub fn load_assets_string(
     }
 }
 
+/// Used when the font can't be loaded from the assets, so the text always looks the same.
+const EMBEDDED_FONT: &[u8] = include_bytes!("../assets/font.ttf");
+
 pub fn load_assets_font(
+    asset_name: &str,
+    assets_path: PathBuf,
+    pack_path: Option<PathBuf>,
+    error_logs: &mut ErrorLogs,
+) -> Font {
+    if let Some(font) = load_assets_font_file(asset_name, assets_path, pack_path, error_logs) {
+        return font;
+    }
+
+    match load_ttf_font_from_bytes(EMBEDDED_FONT) {
+        Ok(font) => return font,
+        Err(err) => {
+            let error_message = format!("Failed to load embedded font: {err}");
+            error_logs.add_error(&error_message);
+            panic!("{error_message}")
+        }
+    }
+}
+
+fn load_assets_font_file(
     asset_name: &str,
     mut assets_path: PathBuf,
     pack_path: Option<PathBuf>,
@@ -194,6 +411,7 @@ pub fn load_shadow_material(
                 uniforms: vec![
                     UniformDesc::new("in_shadow", UniformType::Float1),
                     UniformDesc::new("shadow_strength", UniformType::Float1),
+                    UniformDesc::new("shadow_color", UniformType::Float3),
                 ],
                 pipeline_params: PipelineParams {
                     color_blend: Some(BlendState::new(
@@ -222,6 +440,7 @@ pub fn load_shadow_material(
             uniforms: vec![
                 UniformDesc::new("in_shadow", UniformType::Float1),
                 UniformDesc::new("shadow_strength", UniformType::Float1),
+                UniformDesc::new("shadow_color", UniformType::Float3),
             ],
             pipeline_params: PipelineParams {
                 color_blend: Some(BlendState::new(
@@ -243,9 +462,31 @@ pub fn load_shadow_material(
     };
 }
 
+pub fn get_ball_blend_state(blend_mode: BallBlendMode) -> BlendState {
+    match blend_mode {
+        BallBlendMode::Alpha => BlendState::new(
+            Equation::Add,
+            BlendFactor::Value(BlendValue::SourceAlpha),
+            BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+        ),
+        BallBlendMode::Additive => BlendState::new(
+            Equation::Add,
+            BlendFactor::Value(BlendValue::SourceAlpha),
+            BlendFactor::One,
+        ),
+        // The shader makes the see-through parts white, since the alpha isn't used here.
+        BallBlendMode::Multiply => BlendState::new(
+            Equation::Add,
+            BlendFactor::Value(BlendValue::DestinationColor),
+            BlendFactor::Zero,
+        ),
+    }
+}
+
 pub fn load_ball_material(
     assets_path: PathBuf,
     pack_path: Option<PathBuf>,
+    blend_mode: BallBlendMode,
     error_logs: &mut ErrorLogs,
 ) -> Material {
     if let Some(fragment) = load_assets_string("ball.frag", assets_path, pack_path, error_logs) {
@@ -267,13 +508,13 @@ pub fn load_ball_material(
                     UniformDesc::new("ambient_light", UniformType::Float1),
                     UniformDesc::new("specular_focus", UniformType::Float1),
                     UniformDesc::new("specular_strength", UniformType::Float1),
+                    UniformDesc::new("light_angle", UniformType::Float1),
+                    UniformDesc::new("light_height", UniformType::Float1),
+                    UniformDesc::new("smooth_edge", UniformType::Float1),
+                    UniformDesc::new("multiply", UniformType::Float1),
                 ],
                 pipeline_params: PipelineParams {
-                    color_blend: Some(BlendState::new(
-                        Equation::Add,
-                        BlendFactor::Value(BlendValue::SourceAlpha),
-                        BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
-                    )),
+                    color_blend: Some(get_ball_blend_state(blend_mode)),
                     ..Default::default()
                 },
                 ..Default::default()
@@ -304,12 +545,39 @@ pub fn load_ball_material(
                 UniformDesc::new("ambient_light", UniformType::Float1),
                 UniformDesc::new("specular_focus", UniformType::Float1),
                 UniformDesc::new("specular_strength", UniformType::Float1),
+                UniformDesc::new("light_angle", UniformType::Float1),
+                UniformDesc::new("light_height", UniformType::Float1),
+                UniformDesc::new("smooth_edge", UniformType::Float1),
             ],
+            pipeline_params: PipelineParams {
+                color_blend: Some(get_ball_blend_state(blend_mode)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    ) {
+        Ok(material) => return material,
+        Err(err) => {
+            let error_message = format!("Failed to create ball material: {err}");
+            error_logs.add_error(&error_message);
+            panic!("{error_message}")
+        }
+    };
+}
+
+/// Adds the texture on top of what's behind it, which makes it look like light reflecting off glass.
+pub fn load_glass_material(error_logs: &mut ErrorLogs) -> Material {
+    match load_material(
+        ShaderSource::Glsl {
+            vertex: VERTEX,
+            fragment: FRAGMENT,
+        },
+        MaterialParams {
             pipeline_params: PipelineParams {
                 color_blend: Some(BlendState::new(
                     Equation::Add,
                     BlendFactor::Value(BlendValue::SourceAlpha),
-                    BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+                    BlendFactor::One,
                 )),
                 ..Default::default()
             },
@@ -318,7 +586,7 @@ pub fn load_ball_material(
     ) {
         Ok(material) => return material,
         Err(err) => {
-            let error_message = format!("Failed to create ball material: {err}");
+            let error_message = format!("Failed to create glass material: {err}");
             error_logs.add_error(&error_message);
             panic!("{error_message}")
         }
@@ -329,22 +597,47 @@ impl GameAssets {
     pub fn new(
         pack_path: Option<PathBuf>,
         missing_texture: Texture2D,
+        ball_blend_mode: BallBlendMode,
+        texture_filter: TextureFilter,
+        language: &str,
         error_logs: &mut ErrorLogs,
     ) -> Self {
-        let assets_path = PathBuf::from("./assets");
+        let assets_path = get_game_path("assets");
+
+        // Without the assets folder every single asset would fail to load and show its own error.
+        // Only show one error for the folder, the rest will still be written to the log file.
+        let mut hidden_error_logs;
+        let error_logs = if assets_path.is_dir() {
+            error_logs
+        } else {
+            error_logs.display_error(
+                "The \"assets\" folder was not found next to the executable. Make sure the game was extracted with all of its folders."
+                    .to_string(),
+            );
+            hidden_error_logs = ErrorLogs::new();
+            &mut hidden_error_logs
+        };
+
+        let box_background_texture = load_texture(
+            "box_background.png",
+            assets_path.clone(),
+            pack_path.clone(),
+            &missing_texture,
+            texture_filter.filter_mode(),
+            error_logs,
+        );
+        let box_background_loaded =
+            box_background_texture.raw_miniquad_id() != missing_texture.raw_miniquad_id();
+
         Self {
-            box_background_texture: load_texture(
-                "box_background.png",
-                assets_path.clone(),
-                pack_path.clone(),
-                &missing_texture,
-                error_logs,
-            ),
+            box_background_texture,
+            box_background_loaded,
             box_side_texture: load_texture(
                 "box_side.png",
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                texture_filter.filter_mode(),
                 error_logs,
             ),
             menu_background: load_texture(
@@ -352,6 +645,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             menu_button: load_texture(
@@ -359,6 +653,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             slider_background: load_texture(
@@ -366,6 +661,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             slider_bar: load_texture(
@@ -373,6 +669,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             mouse_normal: load_texture(
@@ -380,6 +677,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             mouse_normal_move: load_texture(
@@ -387,6 +685,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             mouse_hold: load_texture(
@@ -394,6 +693,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             mouse_hold_move: load_texture(
@@ -401,6 +701,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             esc_normal: load_texture(
@@ -408,6 +709,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             esc_hold: load_texture(
@@ -415,6 +717,7 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
                 error_logs,
             ),
             slash: load_texture(
@@ -422,22 +725,42 @@ impl GameAssets {
                 assets_path.clone(),
                 pack_path.clone(),
                 &missing_texture,
+                FilterMode::Linear,
+                error_logs,
+            ),
+            glass_overlay: load_optional_texture(
+                "glass_overlay.png",
+                assets_path.clone(),
+                pack_path.clone(),
+                error_logs,
+            ),
+            ball_material: load_ball_material(
+                assets_path.clone(),
+                pack_path.clone(),
+                ball_blend_mode,
                 error_logs,
             ),
-            ball_material: load_ball_material(assets_path.clone(), pack_path.clone(), error_logs),
             shadow_material: load_shadow_material(
                 assets_path.clone(),
                 pack_path.clone(),
                 error_logs,
             ),
-            font: load_assets_font("font.ttf", assets_path, pack_path, error_logs),
+            glass_material: load_glass_material(error_logs),
+            font: load_assets_font(
+                "font.ttf",
+                assets_path.clone(),
+                pack_path.clone(),
+                error_logs,
+            ),
+            pack_settings: PackSettings::load(pack_path.clone(), error_logs),
+            translations: Translations::load(language, assets_path, pack_path, error_logs),
             missing_texture,
         }
     }
 }
 
 pub fn list_available_packs(error_logs: &mut ErrorLogs) -> Vec<(String, PathBuf)> {
-    let read_dir = match fs::read_dir("./asset_packs") {
+    let read_dir = match fs::read_dir(get_game_path("asset_packs")) {
         Ok(read_dir) => read_dir,
         Err(err) => {
             error_logs.display_error(format!("Failed to read the \"asset_packs\" folder: {err}"));
@@ -473,7 +796,14 @@ pub fn list_available_packs(error_logs: &mut ErrorLogs) -> Vec<(String, PathBuf)
         .collect()
 }
 
-pub fn find_pack(current_string: &str, error_logs: &mut ErrorLogs) -> Option<(String, PathBuf)> {
+/// Returns info for an asset pack in which the input matches its name.
+///
+/// Picks the pack with the longer name.
+pub fn find_pack(
+    current_string: &str,
+    match_mode: MatchMode,
+    error_logs: &mut ErrorLogs,
+) -> Option<(String, PathBuf)> {
     if current_string.is_empty() {
         return None;
     }
@@ -481,7 +811,7 @@ pub fn find_pack(current_string: &str, error_logs: &mut ErrorLogs) -> Option<(St
     let mut selected_pack: Option<(String, PathBuf)> = None;
 
     for (pack_name, pack_path) in list_available_packs(error_logs) {
-        if current_string.ends_with(&pack_name.to_ascii_lowercase()) {
+        if match_mode.matches(current_string, &pack_name.to_ascii_lowercase()) {
             if let Some((selected_pack_name, _)) = &selected_pack {
                 if selected_pack_name.len() > pack_name.len() {
                     continue;
@@ -1,45 +1,299 @@
 use std::f32::consts::{E, PI};
 
+use circular_buffer::CircularBuffer;
 use macroquad::{
-    audio::{play_sound, PlaySoundParams, Sound},
-    color::WHITE,
-    math::{vec2, FloatExt, Vec2},
+    audio::{play_sound, set_sound_volume, stop_sound, PlaySoundParams},
+    color::{Color, WHITE},
+    math::{vec2, FloatExt, Vec2, Vec3},
     prelude::{gl_use_default_material, gl_use_material},
     texture::{draw_texture_ex, DrawTextureParams, Texture2D},
+    time::get_time,
 };
 
-use crate::{assets::GameAssets, Settings};
+use crate::{
+    assets::GameAssets,
+    settings::{BallBlendMode, DragModel, VolumeCurve},
+    sounds::SoundPack,
+    Settings,
+};
+
+/// How long a sound counts as playing when limiting the amount of voices.
+const VOICE_DURATION: f64 = 0.25;
+const MAX_VOICES: usize = 16;
+/// How fast the ball needs to move horizontally before it flips, so it doesn't flicker when it's almost still.
+const FLIP_SPEED: f32 = 50.0;
+/// How many pixels the edge of the ball fades out over with `smooth_edge` turned on.
+const SMOOTH_EDGE_WIDTH: f32 = 4.0;
+const QUADRATIC_DRAG_SPEED: f32 = 1000.0;
+/// How slow the ball can move and still count as resting, in pixels per second.
+const IDLE_SPEED: f32 = 10.0;
+/// How long the ball has to rest before it starts bobbing.
+const IDLE_WAIT: f32 = 1.5;
+/// How fast the bobbing fades in and out.
+const IDLE_FADE_SPEED: f32 = 2.0;
+/// How many times per second the ball bobs.
+const IDLE_BOB_FREQUENCY: f64 = 0.5;
+/// How hard the magnet pulls the ball towards its wall, compared to gravity. A throw harder than this can pull the ball off.
+const MAGNET_STRENGTH: f32 = 25.0;
+/// How much of the magnet pull is left when the ball is far away from the wall, so it still finds its way back.
+const MAGNET_FAR_PULL: f32 = 0.3;
+/// How quickly the ball stops moving when it's stuck to the magnet wall.
+const MAGNET_DAMPING: f32 = 8.0;
+
+/// How many obstacles can be placed. Each one needs its own id for remembering what the ball hit last.
+pub const MAX_OBSTACLES: usize = 32;
+/// The id of the first obstacle. 1 to 4 are the walls and 5 to 8 are the corners.
+const FIRST_OBSTACLE_ID: u8 = 9;
+/// How far into a wall the ball can sink with soft collisions, relative to its radius, before the wall stops it.
+const SOFT_COLLISION_MAX_SINK: f32 = 0.5;
+/// Soft collisions get unstable when the spring is too stiff for the delta time, so the stiffness is limited to stay below this.
+const SOFT_COLLISION_MAX_OMEGA_DT: f32 = 1.5;
+/// The ball only counts as rolling if it's moving away from or into the wall slower than this.
+const ROLL_MAX_NORMAL_SPEED: f32 = 50.;
+/// The rolling sound is silent below this speed, and gets louder until `ROLL_FULL_SPEED`.
+const ROLL_MIN_SPEED: f32 = 20.;
+const ROLL_FULL_SPEED: f32 = 1500.;
+
+/// A bounce against a wall, reported so effects can be spawned where it happened.
+pub struct Impact {
+    /// Where the ball touched the wall.
+    pub position: Vec2,
+    /// Pointing away from the wall.
+    pub direction: Vec2,
+    pub speed: f32,
+}
+
+/// The walls the ball bounces against.
+pub struct Walls {
+    pub center: Vec2,
+    pub half_size: Vec2,
+    /// How far in from the walls the ball has to stay.
+    pub offset: f32,
+    /// The radius of the rounded corners on the inside of the walls. 0 means sharp corners.
+    pub corner_radius: f32,
+}
+
+impl Walls {
+    /// The walls of the box itself.
+    pub fn from_box(box_size: Vec2, settings: &Settings) -> Walls {
+        Walls {
+            center: Vec2::ZERO,
+            half_size: box_size,
+            offset: (settings.box_thickness + settings.box_depth) as f32,
+            corner_radius: settings.box_corner_radius as f32,
+        }
+    }
+
+    /// The rounded corner the ball is in, as the center of the arc, how far from it the ball can go, and the id of the corner.
+    ///
+    /// `position` is relative to the center of the walls. The ball is only in a corner when it's past the arc center on both axes,
+    /// and it can only reach the arc if the corner is rounder than the ball.
+    pub fn rounded_corner(&self, position: Vec2, ball_radius: f32) -> Option<(Vec2, f32, u8)> {
+        let max_corner_radius = (self.half_size - self.offset).min_element().max(0.);
+        let corner_radius = self.corner_radius.min(max_corner_radius);
+        let arc_distance = corner_radius - ball_radius;
+
+        if arc_distance <= 0. {
+            return None;
+        }
 
-const MIN_SOUND_TIME: f32 = 1.0 / 60.0;
+        let corner_sign = vec2(position.x.signum(), position.y.signum());
+        let arc_center = (self.half_size - self.offset - corner_radius) * corner_sign;
+        let from_arc_center = position - arc_center;
+
+        if from_arc_center.x * corner_sign.x <= 0. || from_arc_center.y * corner_sign.y <= 0. {
+            return None;
+        }
+
+        // Floor right, floor left, ceiling right, ceiling left.
+        let corner_id = match (corner_sign.y > 0., corner_sign.x > 0.) {
+            (true, true) => 5,
+            (true, false) => 6,
+            (false, true) => 7,
+            (false, false) => 8,
+        };
+
+        return Some((arc_center, arc_distance, corner_id));
+    }
+}
 
 pub struct Ball {
-    position: Vec2,
-    velocity: Vec2,
-    rotation: f32,
-    rotation_velocity: f32,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub rotation: f32,
+    pub rotation_velocity: f32,
+    facing_left: bool,
+    /// How long the ball has been resting.
+    idle_time: f32,
+    /// How much of the idle bobbing is shown, from 0 to 1.
+    idle_amount: f32,
     vertical_sound_timer: f32,
     horizontal_sound_timer: f32,
     pub radius: f32,
     pub texture: Texture2D,
-    pub sounds: Vec<Sound>,
+    sounds: SoundPack,
+    /// How fast the ball is rolling or sliding along the walls it's resting on.
+    roll_speed: f32,
+    /// How fast the ball is moving, not counting it pushing into whatever it's resting on.
+    moving_speed: f32,
+    /// If the rolling sound is playing.
+    rolling: bool,
+    pub muted: bool,
+    /// Makes the ball fall up. Only lasts until the game is closed.
+    pub gravity_flipped: bool,
+    /// When the most recent sounds started playing.
+    voice_start_times: CircularBuffer<MAX_VOICES, f64>,
 }
 
 impl Ball {
-    pub fn new(texture: Texture2D, radius: f32, sounds: Vec<Sound>) -> Ball {
+    /// Creates a new ball. `rotation` is in degrees.
+    pub fn new(texture: Texture2D, radius: f32, rotation: f32, sounds: SoundPack) -> Ball {
         Ball {
             position: Vec2::new(0., 0.),
             velocity: Vec2::ZERO,
-            rotation: 0.,
+            rotation: rotation.to_radians(),
             rotation_velocity: 0.,
+            facing_left: false,
+            idle_time: 0.,
+            idle_amount: 0.,
             vertical_sound_timer: 0.,
             horizontal_sound_timer: 0.,
             radius,
             texture,
             sounds,
+            roll_speed: 0.,
+            moving_speed: 0.,
+            rolling: false,
+            muted: false,
+            gravity_flipped: false,
+            voice_start_times: CircularBuffer::new(),
+        }
+    }
+
+    /// Changes the sound pack, stopping the rolling sound of the old one.
+    pub fn set_sounds(&mut self, sounds: SoundPack) {
+        if self.rolling {
+            if let Some(roll) = &self.sounds.roll {
+                stop_sound(roll);
+            }
+            self.rolling = false;
+        }
+        self.sounds = sounds;
+    }
+
+    /// Sets the volume of the hit sounds.
+    pub fn set_hit_volume(&self, volume: f32) {
+        for sound in self.sounds.hits.iter() {
+            set_sound_volume(sound, volume);
+        }
+    }
+
+    /// If the ball is lying still, or close enough to it.
+    pub fn is_resting(&self) -> bool {
+        self.moving_speed < IDLE_SPEED
+    }
+
+    /// Starts, stops and changes the volume of the rolling sound depending on how fast the ball is rolling.
+    ///
+    /// `active` should be false when the physics aren't running, like when the ball is paused or grabbed.
+    pub fn update_roll_sound(&mut self, settings: &Settings, active: bool) {
+        let Some(roll) = &self.sounds.roll else {
+            return;
+        };
+
+        let volume = if active && settings.roll_sound && !self.muted {
+            ((self.roll_speed - ROLL_MIN_SPEED) / (ROLL_FULL_SPEED - ROLL_MIN_SPEED)).clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        if volume > 0. {
+            if self.rolling {
+                set_sound_volume(roll, volume * settings.audio_volume);
+            } else {
+                play_sound(
+                    roll,
+                    PlaySoundParams {
+                        looped: true,
+                        volume: volume * settings.audio_volume,
+                    },
+                );
+                self.rolling = true;
+            }
+        } else if self.rolling {
+            stop_sound(roll);
+            self.rolling = false;
+        }
+    }
+
+    /// Moves the ball to a position and stops it. The rotation gets reset to the starting rotation.
+    /// The position gets clamped so the ball stays inside the walls.
+    pub fn spawn_at(&mut self, position: Vec2, settings: &Settings, box_size: Vec2) {
+        let box_offset = (settings.box_thickness + settings.box_depth) as f32;
+        let max_position = (box_size - self.radius - box_offset).max(Vec2::ZERO);
+
+        self.position = position.clamp(-max_position, max_position);
+        self.velocity = Vec2::ZERO;
+        self.rotation = settings.ball_start_rotation.to_radians();
+        self.rotation_velocity = 0.;
+    }
+
+    /// Moves the ball towards the position while it's being held, and sets the velocity so it can be thrown when let go.
+    pub fn move_grabbed(
+        &mut self,
+        position: Vec2,
+        settings: &Settings,
+        box_size: Vec2,
+        delta_time: f32,
+    ) {
+        // Smooths out the velocity a bit so it doesn't stop dead when the mouse didn't move for a single frame.
+        const GRAB_VELOCITY_SMOOTHING: f32 = 0.5;
+
+        let box_offset = (settings.box_thickness + settings.box_depth) as f32;
+        let max_position = (box_size - self.radius - box_offset).max(Vec2::ZERO);
+        let new_position = position.clamp(-max_position, max_position);
+
+        if delta_time != 0.0 {
+            let velocity = (new_position - self.position) / delta_time;
+            self.velocity = self.velocity.lerp(velocity, GRAB_VELOCITY_SMOOTHING);
         }
+
+        self.position = new_position;
+        self.rotation_velocity = 0.;
+
+        // It's held, so it shouldn't bob.
+        self.moving_speed = f32::INFINITY;
+        self.update_animation(delta_time);
+    }
+
+    /// Updates what's only used for drawing the ball, which is the way it faces and how much it bobs.
+    fn update_animation(&mut self, dt: f32) {
+        if self.velocity.x < -FLIP_SPEED {
+            self.facing_left = true;
+        } else if self.velocity.x > FLIP_SPEED {
+            self.facing_left = false;
+        }
+
+        if self.is_resting() {
+            self.idle_time += dt;
+        } else {
+            self.idle_time = 0.;
+        }
+        let target_idle_amount = if self.idle_time > IDLE_WAIT { 1. } else { 0. };
+        let max_idle_change = dt * IDLE_FADE_SPEED;
+        self.idle_amount +=
+            (target_idle_amount - self.idle_amount).clamp(-max_idle_change, max_idle_change);
     }
 
     /// Runs the physics for the ball. Returns the deltatime that is left to be simulated.
+    ///
+    /// `walls_contacted` gets set for every wall the ball touched, in the order floor, ceiling, right, left.
+    ///
+    /// If `cursor_position` is set, the ball gets pulled towards it.
+    ///
+    /// `obstacles` are circles the ball bounces off, given as a position in the box and a radius.
+    ///
+    /// `was_reset` gets set if the physics broke and the ball had to be put back in the middle of the box.
     pub fn step(
         &mut self,
         dt: f32,
@@ -47,11 +301,18 @@ impl Ball {
         visual_box_velocity: Vec2,
         smoothed_box_velocity: Vec2,
         wall_hits: &mut [u8; 2],
-        box_size: Vec2,
+        walls_contacted: &mut [bool; 4],
+        impacts: &mut Vec<Impact>,
+        walls: &Walls,
+        obstacles: &[(Vec2, f32)],
+        cursor_position: Option<Vec2>,
+        was_reset: &mut bool,
     ) -> f32 {
-        let box_thickness = settings.box_thickness as f32;
-        let box_depth = settings.box_depth as f32;
-        let box_offset = box_thickness + box_depth;
+        let box_size = walls.half_size;
+        let box_offset = walls.offset;
+
+        // The physics act like the walls are centered, so the ball gets moved back at the end.
+        self.position -= walls.center;
 
         let temp = wall_hits[0];
         wall_hits[0] = wall_hits[1];
@@ -66,8 +327,55 @@ impl Ball {
 
         // Do physics calculations
 
-        let velocity_acceleration = Vec2::new(0., settings.gravity_strength * 1000.) * dt
-            - self.velocity * (settings.air_friction * dt.clamp(0., 1.));
+        let mut drag = match settings.drag_model {
+            DragModel::Linear => settings.air_friction * dt.clamp(0., 1.),
+            // Quadratic drag matches linear drag when the ball is moving at QUADRATIC_DRAG_SPEED.
+            DragModel::Quadratic => {
+                (settings.air_friction * self.velocity.length() / QUADRATIC_DRAG_SPEED * dt)
+                    .clamp(0., 1.)
+            }
+        };
+
+        let gravity_strength = if self.gravity_flipped {
+            -settings.gravity_strength
+        } else {
+            settings.gravity_strength
+        };
+
+        // How far down the box the ball is, from -1 at the top to 1 at the bottom.
+        // The gradient makes gravity stronger on the side it pulls towards, so it still works when it's flipped.
+        let height = (self.position.y / box_size.y).clamp(-1., 1.);
+        let gravity_scale =
+            (1. + settings.gravity_gradient * height * gravity_strength.signum()).max(0.);
+
+        let mut gravity = Vec2::new(0., gravity_strength * gravity_scale * 1000.);
+
+        if let Some(cursor_position) = cursor_position {
+            let to_cursor = cursor_position - walls.center - self.position;
+            // Pulls less when the cursor is inside the ball, so it doesn't shake around the cursor.
+            let closeness = (to_cursor.length() / self.radius.max(1.)).min(1.);
+            gravity += to_cursor.normalize_or_zero()
+                * settings.cursor_gravity_strength
+                * 1000.
+                * closeness;
+        }
+
+        if let Some(direction) = settings.magnet_wall.direction() {
+            // How far the ball is from touching the wall it's attracted to.
+            let distance = (box_size * direction.abs()).element_sum()
+                - wall_and_ball_offset
+                - self.position.dot(direction);
+            // 1 when touching the wall, going down to 0 when it's one ball radius away.
+            let closeness = 1. - (distance / self.radius.max(1.)).clamp(0., 1.);
+            let pull = MAGNET_FAR_PULL + (1. - MAGNET_FAR_PULL) * closeness;
+            gravity += direction * MAGNET_STRENGTH * 1000. * pull;
+            drag = (drag + MAGNET_DAMPING * closeness * dt).min(1.);
+        }
+
+        let velocity_acceleration = gravity * dt - self.velocity * drag;
+
+        // Like air friction, but for the spin.
+        self.rotation_velocity *= 1. - (settings.spin_friction * dt).clamp(0., 1.);
 
         self.velocity += velocity_acceleration * 0.5;
         if self.velocity.length() > settings.max_velocity * 1000. {
@@ -83,6 +391,14 @@ impl Ball {
 
         let smoothed_total_velocity = self.velocity + smoothed_box_velocity;
 
+        // With soft collisions the ball can sink into the walls, so they only stop it once it's in too far.
+        let sink = if settings.soft_collision {
+            self.radius * SOFT_COLLISION_MAX_SINK
+        } else {
+            0.
+        };
+        let wall_and_ball_offset = wall_and_ball_offset - sink;
+
         let mut back_amount = 0.0_f32;
         let mut back_vec = vec2(0., 0.);
 
@@ -94,8 +410,10 @@ impl Ball {
         // Check how far the ball got into any walls and save the amount it needs to travel back to unintersect.
         // The highest back travel will be used, unless the ball hit a wall the previous step, and hit the same wall again.
         // That wall will not be counted.
+        // In a rounded corner the ball always reaches the arc before the flat walls, so only the arc counts there.
+        let rounded_corner = walls.rounded_corner(self.position, self.radius);
 
-        if distance_to_floor <= 0. {
+        if distance_to_floor <= 0. && rounded_corner.is_none() {
             // Floor
             let back_for_axis = back_amount.max(
                 1.0 - calculate_normalized_pos(
@@ -109,7 +427,7 @@ impl Ball {
                 back_amount = back_for_axis
             }
         }
-        if distance_to_ceiling <= 0. {
+        if distance_to_ceiling <= 0. && rounded_corner.is_none() {
             // Ceiling
             let back_for_axis = back_amount.max(
                 1.0 - calculate_normalized_pos(
@@ -123,7 +441,7 @@ impl Ball {
                 back_amount = back_for_axis
             }
         }
-        if distance_to_right_wall <= 0. {
+        if distance_to_right_wall <= 0. && rounded_corner.is_none() {
             // Right
             let back_for_axis = back_amount.max(
                 1.0 - calculate_normalized_pos(
@@ -138,7 +456,7 @@ impl Ball {
             }
         }
 
-        if distance_to_left_wall <= 0. {
+        if distance_to_left_wall <= 0. && rounded_corner.is_none() {
             // Left
             let back_for_axis = back_amount.max(
                 1.0 - calculate_normalized_pos(
@@ -153,6 +471,36 @@ impl Ball {
             }
         }
 
+        if let Some((arc_center, arc_distance, corner_id)) = rounded_corner {
+            if !wall_hits.contains(&corner_id) {
+                if let Some(exit_time) = calculate_circle_exit_time(
+                    old_position,
+                    self.position,
+                    arc_center,
+                    arc_distance,
+                ) {
+                    back_amount = back_amount.max(1.0 - exit_time);
+                }
+            }
+        }
+
+        // Obstacles get checked along the whole movement, so a fast ball can't pass through a small one.
+        // Only the movement gets moved back, so the ball can still roll along an obstacle it's touching.
+        for (index, (obstacle_position, obstacle_radius)) in obstacles.iter().enumerate() {
+            if wall_hits.contains(&(FIRST_OBSTACLE_ID + index as u8)) {
+                continue;
+            }
+
+            if let Some(hit_time) = calculate_circle_hit_time(
+                old_position,
+                self.position,
+                *obstacle_position - walls.center,
+                self.radius + obstacle_radius,
+            ) {
+                back_amount = back_amount.max(1.0 - hit_time);
+            }
+        }
+
         // Move the ball back and get new delta time.
 
         let new_dt = dt * (1.0 - back_amount);
@@ -177,25 +525,126 @@ impl Ball {
         let distance_to_left_wall = self.position.x + box_size.x - wall_and_ball_offset;
 
         // Putting this to 0 seems to work fine. But just in case, I will put a small number above 0.
+        // With big boxes the positions lose precision, so the number has to grow with the box.
+        // It only starts growing above a box size of about 840, so normal boxes work like before.
         const SMALL_NUMBER: f32 = 0.0001;
+        let collision_epsilon = SMALL_NUMBER.max(box_size.max_element() * f32::EPSILON);
 
         let mut new_last_hit_wall = wall_hits[0];
 
+        // The ceiling counts as a wall.
+        let floor_friction = (settings.ball_friction * settings.floor_friction_mult).min(1.);
+        let wall_friction = (settings.ball_friction * settings.wall_friction_mult).min(1.);
+
+        // Soft collisions push the ball out of the walls with a spring instead of bouncing it off right away.
+        if settings.soft_collision && new_dt > 0. {
+            let stiffness = settings
+                .soft_collision_stiffness
+                .min((SOFT_COLLISION_MAX_OMEGA_DT / new_dt).powi(2));
+            // Bounciness decides how much of the speed the spring gives back, with 0 being critically damped.
+            let damping = 2. * stiffness.sqrt() * (1. - settings.ball_bounciness).clamp(0., 1.);
+
+            // Floor, ceiling, right, left. The normals point into the wall.
+            let soft_walls = [
+                (
+                    vec2(0., 1.),
+                    sink - distance_to_floor,
+                    floor_friction,
+                    false,
+                ),
+                (
+                    vec2(0., -1.),
+                    sink - distance_to_ceiling,
+                    wall_friction,
+                    true,
+                ),
+                (
+                    vec2(1., 0.),
+                    sink - distance_to_right_wall,
+                    wall_friction,
+                    true,
+                ),
+                (
+                    vec2(-1., 0.),
+                    sink - distance_to_left_wall,
+                    wall_friction,
+                    false,
+                ),
+            ];
+
+            for (index, (normal, depth, friction, flip)) in soft_walls.into_iter().enumerate() {
+                if depth <= 0. {
+                    continue;
+                }
+
+                let wall_id = index as u8 + 1;
+                walls_contacted[index] = true;
+
+                let normal_speed = (self.velocity + smoothed_box_velocity).dot(normal);
+
+                if normal_speed > 0. && !wall_hits.contains(&wall_id) {
+                    new_last_hit_wall = wall_id;
+                    hit_wall_speed = hit_wall_speed.max((normal * normal_speed).abs());
+                    impacts.push(Impact {
+                        position: self.position + walls.center + normal * self.radius,
+                        direction: -normal,
+                        speed: normal_speed,
+                    });
+                }
+
+                // The damping can only slow the ball down, it never pulls it into the wall.
+                let push = (stiffness * depth + damping * normal_speed).max(0.);
+                self.velocity -= normal * push * new_dt;
+
+                // Always positive, the direction is handled by `flip` like with the hard walls.
+                let tangent = vec2(normal.y.abs(), normal.x.abs());
+                let tangent_velocity = self.velocity.dot(tangent);
+                let new_tangent_velocity;
+                (self.rotation_velocity, new_tangent_velocity) = calculate_bounce_spin(
+                    tangent_velocity,
+                    visual_box_velocity.dot(tangent),
+                    self.rotation_velocity,
+                    self.radius,
+                    settings.ball_weight,
+                    friction,
+                    flip,
+                );
+                self.velocity += tangent * (new_tangent_velocity - tangent_velocity);
+            }
+        }
+
         // Calculate and apply wall interactions.
         // If it hit the wall the previous step, it will not calculate bounce, but still calculate spin.
         // (I don't remember why I did that but it's probably for a reason.)
 
-        if distance_to_floor <= SMALL_NUMBER {
+        if distance_to_floor <= collision_epsilon {
             // Floor
+            walls_contacted[0] = true;
             hit_wall_speed.y = hit_wall_speed.y.max(smoothed_total_velocity.y.abs());
             self.position.y = box_size.y - wall_and_ball_offset;
 
             if !wall_hits.contains(&1) {
                 new_last_hit_wall = 1;
+                impacts.push(Impact {
+                    position: self.position + walls.center + vec2(0., self.radius),
+                    direction: vec2(0., -1.),
+                    speed: smoothed_total_velocity.y.abs(),
+                });
+                let floor_bounciness = calculate_bounciness(
+                    settings.ball_bounciness,
+                    settings.bounce_falloff,
+                    smoothed_total_velocity.y,
+                );
                 self.velocity.y = self
                     .velocity
                     .y
-                    .min(-self.velocity.y * settings.ball_bounciness - smoothed_box_velocity.y);
+                    .min(-self.velocity.y * floor_bounciness - smoothed_box_velocity.y);
+                self.velocity *= 1. - settings.energy_decay;
+
+                // Keeps the ball bouncing forever, as long as the floor is what it falls onto.
+                if settings.min_bounce_speed > 0. && gravity_strength > 0. {
+                    self.velocity.y = self.velocity.y.min(-settings.min_bounce_speed);
+                }
             }
 
             (self.rotation_velocity, self.velocity.x) = calculate_bounce_spin(
@@ -204,21 +653,33 @@ impl Ball {
                 self.rotation_velocity,
                 self.radius,
                 settings.ball_weight,
-                settings.ball_friction,
+                floor_friction,
                 false,
             );
         }
-        if distance_to_ceiling <= SMALL_NUMBER {
+        if distance_to_ceiling <= collision_epsilon {
             // Ceiling
+            walls_contacted[1] = true;
             hit_wall_speed.y = hit_wall_speed.y.max(smoothed_total_velocity.y.abs());
             self.position.y = -box_size.y + wall_and_ball_offset;
 
             if !wall_hits.contains(&2) {
                 new_last_hit_wall = 2;
+                impacts.push(Impact {
+                    position: self.position + walls.center - vec2(0., self.radius),
+                    direction: vec2(0., 1.),
+                    speed: smoothed_total_velocity.y.abs(),
+                });
+                let ceiling_bounciness = calculate_bounciness(
+                    settings.ball_bounciness,
+                    settings.bounce_falloff,
+                    smoothed_total_velocity.y,
+                );
                 self.velocity.y = self
                     .velocity
                     .y
-                    .max(-self.velocity.y * settings.ball_bounciness - smoothed_box_velocity.y);
+                    .max(-self.velocity.y * ceiling_bounciness - smoothed_box_velocity.y);
+                self.velocity *= 1. - settings.energy_decay;
             }
 
             (self.rotation_velocity, self.velocity.x) = calculate_bounce_spin(
@@ -227,21 +688,33 @@ impl Ball {
                 self.rotation_velocity,
                 self.radius,
                 settings.ball_weight,
-                settings.ball_friction,
+                wall_friction,
                 true,
             );
         }
-        if distance_to_right_wall <= SMALL_NUMBER {
+        if distance_to_right_wall <= collision_epsilon {
             // Right
+            walls_contacted[2] = true;
             hit_wall_speed.x = hit_wall_speed.x.max(smoothed_total_velocity.x.abs());
             self.position.x = box_size.x - wall_and_ball_offset;
 
             if !wall_hits.contains(&3) {
                 new_last_hit_wall = 3;
+                impacts.push(Impact {
+                    position: self.position + walls.center + vec2(self.radius, 0.),
+                    direction: vec2(-1., 0.),
+                    speed: smoothed_total_velocity.x.abs(),
+                });
+                let right_bounciness = calculate_bounciness(
+                    settings.ball_bounciness,
+                    settings.bounce_falloff,
+                    smoothed_total_velocity.x,
+                );
                 self.velocity.x = self
                     .velocity
                     .x
-                    .min(-self.velocity.x * settings.ball_bounciness - smoothed_box_velocity.x);
+                    .min(-self.velocity.x * right_bounciness - smoothed_box_velocity.x);
+                self.velocity *= 1. - settings.energy_decay;
             }
 
             (self.rotation_velocity, self.velocity.y) = calculate_bounce_spin(
@@ -250,22 +723,34 @@ impl Ball {
                 self.rotation_velocity,
                 self.radius,
                 settings.ball_weight,
-                settings.ball_friction,
+                wall_friction,
                 true,
             );
         }
 
-        if distance_to_left_wall <= SMALL_NUMBER {
+        if distance_to_left_wall <= collision_epsilon {
             // Left
+            walls_contacted[3] = true;
             hit_wall_speed.x = hit_wall_speed.x.max(smoothed_total_velocity.x.abs());
             self.position.x = -box_size.x + wall_and_ball_offset;
 
             if !wall_hits.contains(&4) {
                 new_last_hit_wall = 4;
+                impacts.push(Impact {
+                    position: self.position + walls.center - vec2(self.radius, 0.),
+                    direction: vec2(1., 0.),
+                    speed: smoothed_total_velocity.x.abs(),
+                });
+                let left_bounciness = calculate_bounciness(
+                    settings.ball_bounciness,
+                    settings.bounce_falloff,
+                    smoothed_total_velocity.x,
+                );
                 self.velocity.x = self
                     .velocity
                     .x
-                    .max(-self.velocity.x * settings.ball_bounciness - smoothed_box_velocity.x);
+                    .max(-self.velocity.x * left_bounciness - smoothed_box_velocity.x);
+                self.velocity *= 1. - settings.energy_decay;
             }
 
             (self.rotation_velocity, self.velocity.y) = calculate_bounce_spin(
@@ -274,11 +759,134 @@ impl Ball {
                 self.rotation_velocity,
                 self.radius,
                 settings.ball_weight,
-                settings.ball_friction,
+                wall_friction,
                 false,
             );
         }
 
+        // Rounded corners.
+        if let Some((arc_center, arc_distance, corner_id)) =
+            walls.rounded_corner(self.position, self.radius)
+        {
+            let from_arc_center = self.position - arc_center;
+
+            if from_arc_center.length() >= arc_distance - collision_epsilon {
+                let normal = from_arc_center.normalize();
+                let tangent = vec2(normal.y, -normal.x);
+
+                if normal.y > 0. {
+                    walls_contacted[0] = true;
+                } else {
+                    walls_contacted[1] = true;
+                }
+                if normal.x > 0. {
+                    walls_contacted[2] = true;
+                } else {
+                    walls_contacted[3] = true;
+                }
+
+                hit_wall_speed =
+                    hit_wall_speed.max((normal * smoothed_total_velocity.dot(normal)).abs());
+                self.position = arc_center + normal * arc_distance;
+
+                if !wall_hits.contains(&corner_id) {
+                    new_last_hit_wall = corner_id;
+                    impacts.push(Impact {
+                        position: self.position + walls.center + normal * self.radius,
+                        direction: -normal,
+                        speed: smoothed_total_velocity.dot(normal).abs(),
+                    });
+                    let normal_velocity = self.velocity.dot(normal);
+                    let corner_bounciness = calculate_bounciness(
+                        settings.ball_bounciness,
+                        settings.bounce_falloff,
+                        smoothed_total_velocity.dot(normal),
+                    );
+                    let bounced_normal_velocity = normal_velocity.min(
+                        -normal_velocity * corner_bounciness - smoothed_box_velocity.dot(normal),
+                    );
+                    self.velocity += normal * (bounced_normal_velocity - normal_velocity);
+                    self.velocity *= 1. - settings.energy_decay;
+                } else {
+                    // Sliding along the arc. The flat walls get this from moving back, but that doesn't work for a curve.
+                    self.velocity -= normal * self.velocity.dot(normal).max(0.);
+                }
+
+                let tangent_velocity = self.velocity.dot(tangent);
+                let new_tangent_velocity;
+                (self.rotation_velocity, new_tangent_velocity) = calculate_bounce_spin(
+                    tangent_velocity,
+                    visual_box_velocity.dot(tangent),
+                    self.rotation_velocity,
+                    self.radius,
+                    settings.ball_weight,
+                    if normal.y > 0. {
+                        floor_friction
+                    } else {
+                        wall_friction
+                    },
+                    false,
+                );
+                self.velocity += tangent * (new_tangent_velocity - tangent_velocity);
+            }
+        }
+
+        for (index, (obstacle_position, obstacle_radius)) in obstacles.iter().enumerate() {
+            let obstacle_position = *obstacle_position - walls.center;
+            let touch_distance = self.radius + obstacle_radius;
+            let to_obstacle = obstacle_position - self.position;
+
+            if to_obstacle.length() > touch_distance + collision_epsilon {
+                continue;
+            }
+
+            let obstacle_id = FIRST_OBSTACLE_ID + index as u8;
+
+            // Points into the obstacle, the same way the corner normal points into the wall.
+            let normal = to_obstacle.try_normalize().unwrap_or(vec2(0., 1.));
+            let tangent = vec2(normal.y, -normal.x);
+
+            hit_wall_speed =
+                hit_wall_speed.max((normal * smoothed_total_velocity.dot(normal)).abs());
+            self.position = obstacle_position - normal * touch_distance;
+
+            if !wall_hits.contains(&obstacle_id) {
+                new_last_hit_wall = obstacle_id;
+                impacts.push(Impact {
+                    position: self.position + walls.center + normal * self.radius,
+                    direction: -normal,
+                    speed: smoothed_total_velocity.dot(normal).abs(),
+                });
+                let normal_velocity = self.velocity.dot(normal);
+                let obstacle_bounciness = calculate_bounciness(
+                    settings.ball_bounciness,
+                    settings.bounce_falloff,
+                    smoothed_total_velocity.dot(normal),
+                );
+                let bounced_normal_velocity = normal_velocity.min(
+                    -normal_velocity * obstacle_bounciness - smoothed_box_velocity.dot(normal),
+                );
+                self.velocity += normal * (bounced_normal_velocity - normal_velocity);
+                self.velocity *= 1. - settings.energy_decay;
+            } else {
+                // Resting on the obstacle, so gravity shouldn't build up speed going into it.
+                self.velocity -= normal * self.velocity.dot(normal).max(0.);
+            }
+
+            let tangent_velocity = self.velocity.dot(tangent);
+            let new_tangent_velocity;
+            (self.rotation_velocity, new_tangent_velocity) = calculate_bounce_spin(
+                tangent_velocity,
+                visual_box_velocity.dot(tangent),
+                self.rotation_velocity,
+                self.radius,
+                settings.ball_weight,
+                wall_friction,
+                false,
+            );
+            self.velocity += tangent * (new_tangent_velocity - tangent_velocity);
+        }
+
         wall_hits[0] = new_last_hit_wall;
 
         let density = settings.hit_density;
@@ -291,42 +899,129 @@ impl Ball {
 
         if ((horizontal_sound && hit_wall_speed.x > speed_limit)
             || (vertical_sound && hit_wall_speed.y > speed_limit))
-            && !self.sounds.is_empty()
+            && !self.sounds.hits.is_empty()
+            && !self.muted
         {
             let inverted_distances_from_corners =
                 self.position.abs() + vec2(0., box_size.x - box_size.y);
 
-            let mut sound_volume = hit_wall_speed.max_element();
-
-            // The closer to the center it is, the louder the sound.
-            let distance_from_corner = box_size.x - inverted_distances_from_corners.min_element();
-            sound_volume -= speed_limit;
-            sound_volume /= 450.;
-            sound_volume *= 1. + distance_from_corner / 200.;
-            let volume = 1. - 1. / E.powf(sound_volume * sound_volume * density * density);
-            play_sound(
-                &self.sounds[quad_rand::gen_range(0, self.sounds.len())],
-                PlaySoundParams {
-                    looped: false,
-                    volume: volume * settings.audio_volume,
-                },
-            );
+            let hit_speed = hit_wall_speed.max_element();
+
+            let volume = match settings.volume_curve {
+                VolumeCurve::Exponential => {
+                    // The closer to the center it is, the louder the sound.
+                    // Corner emphasis scales how much louder, with 0 making every spot the same.
+                    let distance_from_corner =
+                        box_size.x - inverted_distances_from_corners.min_element();
+                    let mut sound_volume = hit_speed - speed_limit;
+                    sound_volume /= 450.;
+                    sound_volume *= 1. + distance_from_corner / 200. * settings.corner_emphasis;
+                    1. - 1. / E.powf(sound_volume * sound_volume * density * density)
+                }
+                VolumeCurve::Linear => {
+                    let speed_range = (settings.max_hit_speed - speed_limit).max(1.);
+                    ((hit_speed - speed_limit) / speed_range).clamp(0., 1.)
+                }
+            };
+
+            // Too many sounds playing at the same time makes the audio clip.
+            let time = get_time();
+            let active_voices = self
+                .voice_start_times
+                .iter()
+                .filter(|start_time| time - **start_time < VOICE_DURATION)
+                .count();
+
+            if active_voices < settings.max_voices as usize {
+                play_sound(
+                    &self.sounds.hits[quad_rand::gen_range(0, self.sounds.hits.len())],
+                    PlaySoundParams {
+                        looped: false,
+                        volume: volume * settings.audio_volume,
+                    },
+                );
+                self.voice_start_times.push_back(time);
+            }
         }
 
         self.horizontal_sound_timer -= new_dt;
         self.vertical_sound_timer -= new_dt;
 
         if hit_wall_speed.x != 0. {
-            self.horizontal_sound_timer = MIN_SOUND_TIME;
+            self.horizontal_sound_timer = settings.hit_sound_cooldown;
         }
         if hit_wall_speed.y != 0. {
-            self.vertical_sound_timer = MIN_SOUND_TIME;
+            self.vertical_sound_timer = settings.hit_sound_cooldown;
+        }
+
+        // Check if the ball is resting against any walls and how fast it's moving along them, for the rolling sound.
+        let distance_to_floor = box_size.y - wall_and_ball_offset - self.position.y;
+        let distance_to_ceiling = self.position.y + box_size.y - wall_and_ball_offset;
+        let distance_to_right_wall = box_size.x - wall_and_ball_offset - self.position.x;
+        let distance_to_left_wall = self.position.x + box_size.x - wall_and_ball_offset;
+        let touching = |distance: f32| distance <= sink + collision_epsilon;
+        let relative_velocity = self.velocity + visual_box_velocity;
+
+        self.roll_speed = 0.;
+        if (touching(distance_to_floor) || touching(distance_to_ceiling))
+            && relative_velocity.y.abs() < ROLL_MAX_NORMAL_SPEED
+        {
+            self.roll_speed = self.roll_speed.max(relative_velocity.x.abs());
+        }
+        if (touching(distance_to_right_wall) || touching(distance_to_left_wall))
+            && relative_velocity.x.abs() < ROLL_MAX_NORMAL_SPEED
+        {
+            self.roll_speed = self.roll_speed.max(relative_velocity.y.abs());
+        }
+
+        // Gravity keeps adding to the velocity while the ball rests on something, and it keeps bouncing a tiny bit,
+        // so only the movement along what it's touching counts. A real bounce will have it off the wall next step.
+        let mut moving_velocity = relative_velocity;
+        if touching(distance_to_floor) || touching(distance_to_ceiling) {
+            moving_velocity.y = 0.;
+        }
+        if touching(distance_to_right_wall) || touching(distance_to_left_wall) {
+            moving_velocity.x = 0.;
+        }
+        if let Some((arc_center, arc_distance, _)) =
+            walls.rounded_corner(self.position, self.radius)
+        {
+            let from_arc_center = self.position - arc_center;
+            if touching(arc_distance - from_arc_center.length()) {
+                moving_velocity =
+                    moving_velocity.reject_from_normalized(from_arc_center.normalize_or_zero());
+            }
+        }
+        for (obstacle_position, obstacle_radius) in obstacles {
+            let from_obstacle = self.position - (*obstacle_position - walls.center);
+            if touching(from_obstacle.length() - self.radius - obstacle_radius) {
+                moving_velocity =
+                    moving_velocity.reject_from_normalized(from_obstacle.normalize_or_zero());
+            }
+        }
+        self.moving_speed = moving_velocity.length();
+        self.update_animation(new_dt);
+
+        self.position += walls.center;
+
+        // Extreme settings can make the numbers invalid, and then the ball would be gone until the game restarts.
+        if !(self.position.is_finite()
+            && self.velocity.is_finite()
+            && self.rotation.is_finite()
+            && self.rotation_velocity.is_finite())
+        {
+            self.position = walls.center;
+            self.velocity = Vec2::ZERO;
+            self.rotation = settings.ball_start_rotation.to_radians();
+            self.rotation_velocity = 0.;
+            *was_reset = true;
+            return 0.;
         }
 
         return dt - new_dt;
     }
 
-    pub fn render(&mut self, game_assets: &GameAssets, settings: &Settings, box_size: Vec2) {
+    pub fn render(&self, game_assets: &GameAssets, settings: &Settings, box_size: Vec2) {
         let box_thickness = settings.box_thickness as f32;
         let box_depth = settings.box_depth as f32;
         let box_offset = box_thickness + box_depth;
@@ -345,6 +1040,10 @@ impl Ball {
             .shadow_material
             .set_uniform("shadow_strength", settings.shadow_strength);
 
+        game_assets
+            .shadow_material
+            .set_uniform("shadow_color", Vec3::from_array(settings.shadow_color));
+
         game_assets.shadow_material.set_uniform(
             "in_shadow",
             distance_to_floor / self.radius / settings.shadow_distance_strength,
@@ -423,11 +1122,23 @@ impl Ball {
 
         // Draw ball
 
+        // When flipping, the spin is not shown since a spinning sprite that flips looks weird.
+        let rotation = if settings.flip_with_direction {
+            settings.ball_start_rotation.to_radians()
+        } else {
+            self.rotation
+        };
+
+        let flip_x = settings.flip_with_direction && self.facing_left;
+
+        // Only moves the drawn ball, the physics don't know about it.
+        // Goes up from where the ball is resting and back down, so it never sinks into the floor.
+        let bob_wave = (1. - (get_time() * IDLE_BOB_FREQUENCY * PI as f64 * 2.).cos() as f32) / 2.;
+        let idle_offset = bob_wave * settings.idle_bob * self.idle_amount;
+
         gl_use_material(&game_assets.ball_material);
 
-        game_assets
-            .ball_material
-            .set_uniform("rotation", self.rotation);
+        game_assets.ball_material.set_uniform("rotation", rotation);
         game_assets.ball_material.set_uniform(
             "floor_distance",
             distance_to_floor / self.radius / settings.shadow_distance_strength,
@@ -463,23 +1174,130 @@ impl Ball {
         game_assets
             .ball_material
             .set_uniform("specular_strength", settings.specular_strength);
+        game_assets
+            .ball_material
+            .set_uniform("light_angle", settings.light_angle.to_radians());
+        game_assets
+            .ball_material
+            .set_uniform("light_height", settings.light_height);
+        game_assets.ball_material.set_uniform(
+            "smooth_edge",
+            if settings.smooth_edge {
+                SMOOTH_EDGE_WIDTH
+            } else {
+                0.
+            },
+        );
+        game_assets.ball_material.set_uniform(
+            "multiply",
+            if settings.ball_blend_mode == BallBlendMode::Multiply {
+                1.
+            } else {
+                0.
+            },
+        );
 
         draw_texture_ex(
             &self.texture,
             self.position.x - self.radius,
-            self.position.y - self.radius,
+            self.position.y - self.radius - idle_offset,
             WHITE,
             DrawTextureParams {
                 dest_size: Some(vec2(self.radius * 2., self.radius * 2.)),
-                rotation: self.rotation,
+                rotation,
+                flip_x,
                 ..Default::default()
             },
         );
 
+        // The reflection doesn't spin with the ball since the light isn't moving.
+        if let Some(glass_overlay) = &game_assets.glass_overlay {
+            if settings.glass_overlay {
+                gl_use_material(&game_assets.glass_material);
+
+                draw_texture_ex(
+                    glass_overlay,
+                    self.position.x - self.radius,
+                    self.position.y - self.radius - idle_offset,
+                    Color::new(1., 1., 1., settings.glass_overlay_intensity),
+                    DrawTextureParams {
+                        dest_size: Some(vec2(self.radius * 2., self.radius * 2.)),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
         gl_use_default_material();
     }
 }
 
+/// The impact speed where a `bounce_falloff` of 1 halves the bounciness.
+const BOUNCE_FALLOFF_SPEED: f32 = 5000.;
+
+/// Makes the ball bounce less the harder it hits, like a real ball squishing more.
+///
+/// `bounciness / (1 + falloff * impact_speed / BOUNCE_FALLOFF_SPEED)`
+///
+/// With a falloff of 0 the bounciness stays the same at any speed. The result never goes above
+/// the normal bounciness, and is kept between 0 and 1 so a bounce can't add energy.
+fn calculate_bounciness(bounciness: f32, falloff: f32, impact_speed: f32) -> f32 {
+    let falloff = 1. + falloff.max(0.) * impact_speed.abs() / BOUNCE_FALLOFF_SPEED;
+    return (bounciness / falloff).clamp(0., 1.);
+}
+
+/// Returns how far along the movement from `start` to `end` the ball first touches a circle, from 0 to 1.
+///
+/// Returns `None` if it doesn't touch it, or if it's already touching it or moving away from it.
+fn calculate_circle_hit_time(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let movement = end - start;
+    let from_center = start - center;
+
+    let a = movement.dot(movement);
+    let b = 2. * from_center.dot(movement);
+    let c = from_center.dot(from_center) - radius * radius;
+
+    if c <= 0. || b >= 0. || a == 0. {
+        return None;
+    }
+
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return None;
+    }
+
+    let hit_time = (-b - discriminant.sqrt()) / (2. * a);
+    if hit_time > 1. {
+        return None;
+    }
+
+    return Some(hit_time);
+}
+
+/// Returns how far along the movement from `start` to `end` the ball leaves a circle it's inside of, from 0 to 1.
+///
+/// Returns `None` if it doesn't leave it, or if it's already outside of it.
+fn calculate_circle_exit_time(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let movement = end - start;
+    let from_center = start - center;
+
+    let a = movement.dot(movement);
+    let b = 2. * from_center.dot(movement);
+    let c = from_center.dot(from_center) - radius * radius;
+
+    if c > 0. || a == 0. {
+        return None;
+    }
+
+    // Starting inside means the discriminant can't be negative.
+    let exit_time = (-b + (b * b - 4. * a * c).sqrt()) / (2. * a);
+    if exit_time > 1. {
+        return None;
+    }
+
+    return Some(exit_time.max(0.));
+}
+
 fn calculate_normalized_pos(min: f32, max: f32, value: f32) -> f32 {
     if min == max {
         return 0.0;
@@ -519,3 +1337,348 @@ pub fn calculate_bounce_spin(
         current_rotation_direction_velocity - window_velocity,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use macroquad::miniquad::{RawId, TextureId};
+
+    use super::*;
+
+    /// A ball that doesn't need a window, since it never gets rendered and has no sounds.
+    fn test_ball(settings: &Settings) -> Ball {
+        Ball::new(
+            Texture2D::from_miniquad_texture(TextureId::from_raw_id(RawId::OpenGl(0))),
+            settings.ball_radius as f32,
+            settings.ball_start_rotation,
+            SoundPack {
+                hits: Vec::new(),
+                roll: None,
+            },
+        )
+    }
+
+    /// Runs the physics until all of `dt` has been used up. Returns if the ball had to be reset.
+    fn step_ball(ball: &mut Ball, mut dt: f32, settings: &Settings, walls: &Walls) -> bool {
+        let mut wall_hits = [0; 2];
+        let mut was_reset = false;
+        let mut steps = 0;
+        while dt > 0. && steps < 100 {
+            dt = ball.step(
+                dt,
+                settings,
+                Vec2::ZERO,
+                Vec2::ZERO,
+                &mut wall_hits,
+                &mut [false; 4],
+                &mut Vec::new(),
+                walls,
+                &[],
+                None,
+                &mut was_reset,
+            );
+            steps += 1;
+        }
+        return was_reset;
+    }
+
+    fn box_walls(settings: &Settings) -> Walls {
+        Walls::from_box(
+            vec2(settings.box_width as f32, settings.box_height as f32),
+            settings,
+        )
+    }
+
+    #[test]
+    fn normal_settings_dont_reset() {
+        let settings = Settings::default();
+        let mut ball = test_ball(&settings);
+        ball.velocity = vec2(3000., -2000.);
+
+        for _ in 0..600 {
+            assert!(!step_ball(
+                &mut ball,
+                1. / 60.,
+                &settings,
+                &box_walls(&settings)
+            ));
+        }
+        assert!(ball.position.is_finite());
+    }
+
+    #[test]
+    fn infinite_gravity_resets_ball() {
+        let mut settings = Settings::default();
+        settings.gravity_strength = f32::INFINITY;
+        let mut ball = test_ball(&settings);
+
+        assert!(step_ball(
+            &mut ball,
+            1. / 60.,
+            &settings,
+            &box_walls(&settings)
+        ));
+        assert_eq!(ball.position, Vec2::ZERO);
+        assert_eq!(ball.velocity, Vec2::ZERO);
+        assert_eq!(ball.rotation_velocity, 0.);
+    }
+
+    #[test]
+    fn huge_air_friction_resets_ball() {
+        let mut settings = Settings::default();
+        settings.drag_model = DragModel::Quadratic;
+        settings.air_friction = f32::MAX;
+        let mut ball = test_ball(&settings);
+        ball.velocity = vec2(f32::MAX, f32::MAX);
+
+        step_ball(&mut ball, 1. / 60., &settings, &box_walls(&settings));
+        assert!(ball.position.is_finite());
+        assert!(ball.velocity.is_finite());
+        assert!(ball.rotation.is_finite());
+    }
+
+    #[test]
+    fn large_delta_time_stays_in_box() {
+        let settings = Settings::default();
+        let walls = box_walls(&settings);
+        let max_position = walls.half_size - walls.offset - settings.ball_radius as f32;
+        let mut ball = test_ball(&settings);
+        ball.velocity = vec2(20000., -15000.);
+
+        for _ in 0..100 {
+            assert!(!step_ball(
+                &mut ball,
+                settings.max_delta_time,
+                &settings,
+                &walls
+            ));
+            assert!(ball.position.abs().cmple(max_position + 0.01).all());
+        }
+    }
+
+    #[test]
+    fn large_box_floor_contact() {
+        let mut settings = Settings::default();
+        settings.box_width = 1_000_000;
+        settings.box_height = 1_000_000;
+        settings.gravity_strength = 0.;
+        let walls = box_walls(&settings);
+        let floor_position = walls.half_size.y - walls.offset - settings.ball_radius as f32;
+        let mut ball = test_ball(&settings);
+        // As close to the floor as the position can get without touching it. This is way more than 0.0001 here.
+        ball.position.y = f32::from_bits(floor_position.to_bits() - 1);
+
+        let mut walls_contacted = [false; 4];
+        ball.step(
+            1. / 60.,
+            &settings,
+            Vec2::ZERO,
+            Vec2::ZERO,
+            &mut [0; 2],
+            &mut walls_contacted,
+            &mut Vec::new(),
+            &walls,
+            &[],
+            None,
+            &mut false,
+        );
+        assert!(walls_contacted[0]);
+        assert_eq!(ball.position.y, floor_position);
+    }
+
+    /// Shoots the ball diagonally into the bottom right corner.
+    fn hit_corner(settings: &Settings, start_position: Vec2) -> (Ball, [bool; 4], Vec<Impact>) {
+        let walls = box_walls(settings);
+        let mut ball = test_ball(settings);
+        ball.position = start_position;
+        ball.velocity = vec2(4000., 4000.);
+
+        let mut walls_contacted = [false; 4];
+        let mut impacts = Vec::new();
+        let mut wall_hits = [0; 2];
+        let mut steps = 0;
+        while impacts.is_empty() && steps < 100 {
+            ball.step(
+                1. / 60.,
+                settings,
+                Vec2::ZERO,
+                Vec2::ZERO,
+                &mut wall_hits,
+                &mut walls_contacted,
+                &mut impacts,
+                &walls,
+                &[],
+                None,
+                &mut false,
+            );
+            steps += 1;
+        }
+        return (ball, walls_contacted, impacts);
+    }
+
+    #[test]
+    fn rounded_corner_keeps_ball_on_arc() {
+        let mut settings = Settings::default();
+        settings.gravity_strength = 0.;
+        settings.box_corner_radius = 200;
+        let walls = box_walls(&settings);
+        let corner_radius = settings.box_corner_radius as f32;
+        let arc_center = walls.half_size - walls.offset - corner_radius;
+        let arc_distance = corner_radius - settings.ball_radius as f32;
+
+        let (ball, walls_contacted, impacts) = hit_corner(&settings, arc_center + 10.);
+
+        assert_eq!(impacts.len(), 1);
+        assert!((ball.position - arc_center).length() <= arc_distance + 0.01);
+        // Coming in at 45 degrees, it hits the middle of the arc.
+        let normal = (ball.position - arc_center).normalize();
+        assert!(normal.abs_diff_eq(Vec2::splat(0.5f32.sqrt()), 0.01));
+        assert!(impacts[0].direction.abs_diff_eq(-normal, 0.01));
+        // Floor and right wall.
+        assert_eq!(walls_contacted, [true, false, true, false]);
+        // It bounces back out of the corner.
+        assert!(ball.velocity.dot(normal) <= 0.);
+    }
+
+    #[test]
+    fn sharp_corner_stops_ball_at_both_walls() {
+        let mut settings = Settings::default();
+        settings.gravity_strength = 0.;
+        settings.box_corner_radius = 0;
+        let walls = box_walls(&settings);
+        let max_position = walls.half_size - walls.offset - settings.ball_radius as f32;
+
+        let (ball, walls_contacted, _) = hit_corner(&settings, max_position - 100.);
+
+        assert!(ball.position.abs_diff_eq(max_position, 0.01));
+        assert!(walls_contacted[0] && walls_contacted[2]);
+        assert!(ball.velocity.x <= 0. && ball.velocity.y <= 0.);
+    }
+
+    #[test]
+    fn corner_smaller_than_ball_acts_sharp() {
+        let mut settings = Settings::default();
+        settings.gravity_strength = 0.;
+        settings.box_corner_radius = settings.ball_radius / 2;
+        let walls = box_walls(&settings);
+        let max_position = walls.half_size - walls.offset - settings.ball_radius as f32;
+
+        let (ball, _, _) = hit_corner(&settings, max_position - 100.);
+
+        assert!(ball.position.abs_diff_eq(max_position, 0.01));
+    }
+
+    #[test]
+    fn ball_settles_after_rolling_through_rounded_corners() {
+        let mut settings = Settings::default();
+        settings.box_corner_radius = 200;
+        let walls = box_walls(&settings);
+        let mut ball = test_ball(&settings);
+        ball.velocity = vec2(2500., -1000.);
+
+        let mut wall_hits = [0; 2];
+        for _ in 0..1200 {
+            let mut dt = 1. / 60.;
+            let mut steps = 0;
+            while dt > 0. {
+                dt = ball.step(
+                    dt,
+                    &settings,
+                    Vec2::ZERO,
+                    Vec2::ZERO,
+                    &mut wall_hits,
+                    &mut [false; 4],
+                    &mut Vec::new(),
+                    &walls,
+                    &[],
+                    None,
+                    &mut false,
+                );
+                steps += 1;
+                // Going from the arc onto the flat wall used to take a tiny step every time.
+                assert!(steps < 10);
+            }
+
+            if let Some((arc_center, arc_distance, _)) =
+                walls.rounded_corner(ball.position, ball.radius)
+            {
+                assert!((ball.position - arc_center).length() <= arc_distance + 0.01);
+            }
+        }
+
+        // Resting on the floor instead of speeding up into it.
+        assert!(ball.velocity.y.abs() < 100.);
+    }
+
+    #[test]
+    fn ball_rests_on_floor() {
+        let settings = Settings::default();
+        let walls = box_walls(&settings);
+        let mut ball = test_ball(&settings);
+
+        step_ball(&mut ball, 1. / 60., &settings, &walls);
+        assert!(!ball.is_resting());
+
+        for _ in 0..600 {
+            step_ball(&mut ball, 1. / 60., &settings, &walls);
+        }
+        assert!(ball.is_resting());
+    }
+
+    #[test]
+    fn rolling_ball_isnt_resting() {
+        let settings = Settings::default();
+        let walls = box_walls(&settings);
+        let mut ball = test_ball(&settings);
+        ball.position.y = walls.half_size.y - walls.offset - ball.radius;
+        ball.velocity = vec2(200., 0.);
+
+        step_ball(&mut ball, 1. / 60., &settings, &walls);
+        assert!(!ball.is_resting());
+    }
+
+    #[test]
+    fn ball_stays_on_top_of_obstacle() {
+        let settings = Settings::default();
+        let walls = box_walls(&settings);
+        let mut ball = test_ball(&settings);
+        let obstacles = [(vec2(0., 200.), 50.)];
+        let mut wall_hits = [0; 2];
+
+        for _ in 0..600 {
+            let mut dt = 1. / 60.;
+            while dt > 0. {
+                dt = ball.step(
+                    dt,
+                    &settings,
+                    Vec2::ZERO,
+                    Vec2::ZERO,
+                    &mut wall_hits,
+                    &mut [false; 4],
+                    &mut Vec::new(),
+                    &walls,
+                    &obstacles,
+                    None,
+                    &mut false,
+                );
+            }
+        }
+
+        assert!(ball
+            .position
+            .abs_diff_eq(vec2(0., 200. - 50. - ball.radius), 0.01));
+        assert!(ball.velocity.length() < 1.);
+    }
+
+    #[test]
+    fn reset_ball_goes_to_walls_center() {
+        let mut settings = Settings::default();
+        settings.gravity_strength = f32::NAN;
+        let mut walls = box_walls(&settings);
+        walls.center = vec2(120., -80.);
+        let mut ball = test_ball(&settings);
+        ball.position = walls.center;
+
+        assert!(step_ball(&mut ball, 1. / 60., &settings, &walls));
+        assert_eq!(ball.position, walls.center);
+    }
+}
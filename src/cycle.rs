@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use macroquad::time::get_time;
+
+/// How long a listing is used before the folder gets read again, so new files still show up.
+const LISTING_CACHE_DURATION: f64 = 2.0;
+
+/// Steps through the balls or sounds in a folder, one at a time.
+///
+/// The listing is kept for a bit so pressing the key quickly doesn't read the folder every time.
+pub struct AssetCycler {
+    listing: Vec<(String, PathBuf)>,
+    listed_at: Option<f64>,
+}
+
+impl AssetCycler {
+    pub fn new() -> Self {
+        Self {
+            listing: Vec::new(),
+            listed_at: None,
+        }
+    }
+
+    /// Returns the entry `offset` steps away from `current_name`, wrapping around at the ends.
+    ///
+    /// If `current_name` isn't in the listing, it starts from the first or last entry depending on the direction.
+    pub fn cycle(
+        &mut self,
+        current_name: &str,
+        offset: isize,
+        list: impl FnOnce() -> Vec<(String, PathBuf)>,
+    ) -> Option<(String, PathBuf)> {
+        let time = get_time();
+        let is_outdated = self
+            .listed_at
+            .map_or(true, |listed_at| time - listed_at > LISTING_CACHE_DURATION);
+
+        if is_outdated {
+            self.listing = list();
+            // The folder order isn't guaranteed, so sort it to always cycle in the same order.
+            self.listing
+                .sort_by_key(|(name, _)| name.to_ascii_lowercase());
+            self.listed_at = Some(time);
+        }
+
+        if self.listing.is_empty() {
+            return None;
+        }
+
+        let len = self.listing.len() as isize;
+        let current_index = self
+            .listing
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(current_name));
+
+        let index = match current_index {
+            Some(index) => (index as isize + offset).rem_euclid(len),
+            None if offset < 0 => offset.rem_euclid(len),
+            None => (offset - 1).rem_euclid(len),
+        };
+
+        return Some(self.listing[index as usize].clone());
+    }
+}
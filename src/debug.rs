@@ -0,0 +1,228 @@
+use circular_buffer::CircularBuffer;
+use macroquad::{
+    color::Color,
+    math::{vec2, Vec2},
+    shapes::{draw_line, draw_rectangle},
+    text::{draw_text_ex, TextParams},
+};
+
+const DEBUG_PADDING: f32 = 10.0;
+const DEBUG_FONT_SIZE: u16 = 22;
+const DEBUG_FONT_SIZE_F32: f32 = DEBUG_FONT_SIZE as f32;
+const DEBUG_FONT_SCALE: f32 = 2.;
+const DEBUG_LINE_HEIGHT: f32 = DEBUG_FONT_SIZE_F32 * DEBUG_FONT_SCALE;
+const DEBUG_WIDTH: f32 = 600.0;
+
+/// How many frames of ball speed the graph shows.
+const SPEED_SAMPLES: usize = 200;
+const SPEED_GRAPH_HEIGHT: f32 = 200.0;
+
+/// The spacings the grid cycles through, in pixels. After the last one the grid is turned off.
+const GRID_SPACINGS: [f32; 3] = [25.0, 50.0, 100.0];
+const GRID_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.15);
+const GRID_CENTER_COLOR: Color = Color::new(1.0, 0.3, 0.3, 0.6);
+const GRID_CENTER_SIZE: f32 = 20.0;
+
+const WALL_NAMES: [&str; 4] = ["floor", "ceiling", "right", "left"];
+
+/// Overlay with information useful when debugging. Toggled with F3 and not saved in the settings.
+///
+/// Also has a grid for lining things up inside the box, cycled with F4.
+pub struct DebugOverlay {
+    pub enabled: bool,
+    /// Index into `GRID_SPACINGS`, or `None` if the grid is hidden.
+    grid_spacing: Option<usize>,
+    lines: Vec<String>,
+    speed_samples: CircularBuffer<SPEED_SAMPLES, f32>,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            grid_spacing: None,
+            lines: Vec::new(),
+            speed_samples: CircularBuffer::new(),
+        }
+    }
+
+    /// Switches to the next grid spacing, hiding the grid after the biggest one.
+    pub fn cycle_grid(&mut self) {
+        self.grid_spacing = match self.grid_spacing {
+            None => Some(0),
+            Some(index) if index + 1 < GRID_SPACINGS.len() => Some(index + 1),
+            Some(_) => None,
+        };
+    }
+
+    /// Draws grid lines going out from the center of the box, and a cross at the center.
+    ///
+    /// `half_size` is half of the inside of the box.
+    pub fn render_grid(&self, half_size: Vec2) {
+        let Some(index) = self.grid_spacing else {
+            return;
+        };
+        let spacing = GRID_SPACINGS[index];
+
+        let lines_x = (half_size.x / spacing) as i32;
+        for i in -lines_x..=lines_x {
+            let x = i as f32 * spacing;
+            draw_line(x, -half_size.y, x, half_size.y, 1., GRID_COLOR);
+        }
+
+        let lines_y = (half_size.y / spacing) as i32;
+        for i in -lines_y..=lines_y {
+            let y = i as f32 * spacing;
+            draw_line(-half_size.x, y, half_size.x, y, 1., GRID_COLOR);
+        }
+
+        draw_line(
+            -GRID_CENTER_SIZE,
+            0.,
+            GRID_CENTER_SIZE,
+            0.,
+            2.,
+            GRID_CENTER_COLOR,
+        );
+        draw_line(
+            0.,
+            -GRID_CENTER_SIZE,
+            0.,
+            GRID_CENTER_SIZE,
+            2.,
+            GRID_CENTER_COLOR,
+        );
+    }
+
+    /// Adds the speed of the ball this frame to the speed graph.
+    pub fn add_speed_sample(&mut self, speed: f32) {
+        if self.enabled {
+            self.speed_samples.push_back(speed);
+        } else {
+            self.speed_samples.clear();
+        }
+    }
+
+    /// Adds a line that will be shown the next time the overlay gets rendered.
+    pub fn add_line(&mut self, line: String) {
+        if self.enabled {
+            self.lines.push(line);
+        }
+    }
+
+    /// Adds a line listing the walls the ball touched this frame.
+    pub fn add_walls_contacted(&mut self, walls_contacted: &[bool; 4]) {
+        let names = WALL_NAMES
+            .iter()
+            .zip(walls_contacted)
+            .filter(|(_, contacted)| **contacted)
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+
+        self.add_line(format!(
+            "Walls contacted: {} ({})",
+            names.len(),
+            names.join(", ")
+        ));
+    }
+
+    /// Renders the lines that have been added and clears them.
+    pub fn render(&mut self, top_left_corner: Vec2) {
+        if !self.enabled {
+            self.lines.clear();
+            return;
+        }
+
+        let position = top_left_corner + vec2(DEBUG_PADDING, DEBUG_PADDING);
+        let lines_height = self.lines.len() as f32 * DEBUG_LINE_HEIGHT;
+
+        draw_rectangle(
+            position.x,
+            position.y,
+            DEBUG_WIDTH,
+            lines_height + SPEED_GRAPH_HEIGHT + DEBUG_LINE_HEIGHT + DEBUG_PADDING * 3.,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+
+        for (i, line) in self.lines.iter().enumerate() {
+            draw_text_ex(
+                line,
+                position.x + DEBUG_PADDING,
+                position.y + DEBUG_PADDING + (i + 1) as f32 * DEBUG_LINE_HEIGHT
+                    - DEBUG_LINE_HEIGHT * 0.25,
+                TextParams {
+                    font: None,
+                    font_size: DEBUG_FONT_SIZE,
+                    font_scale: DEBUG_FONT_SCALE,
+                    color: Color::new(1.0, 1.0, 1.0, 1.0),
+                    ..Default::default()
+                },
+            );
+        }
+
+        self.render_speed_graph(
+            position + vec2(DEBUG_PADDING, lines_height + DEBUG_PADDING * 2.),
+            DEBUG_WIDTH - DEBUG_PADDING * 2.,
+        );
+
+        self.lines.clear();
+    }
+
+    /// Draws the speed samples as a line going from the oldest to the newest, scaled to the fastest sample.
+    fn render_speed_graph(&self, top_left_corner: Vec2, width: f32) {
+        let max_speed = self
+            .speed_samples
+            .iter()
+            .copied()
+            .fold(0., f32::max)
+            .max(1.);
+
+        draw_text_ex(
+            &format!("Speed (max {:.0})", max_speed),
+            top_left_corner.x,
+            top_left_corner.y + DEBUG_LINE_HEIGHT * 0.75,
+            TextParams {
+                font: None,
+                font_size: DEBUG_FONT_SIZE,
+                font_scale: DEBUG_FONT_SCALE,
+                color: Color::new(1.0, 1.0, 1.0, 1.0),
+                ..Default::default()
+            },
+        );
+
+        let graph_bottom = top_left_corner.y + DEBUG_LINE_HEIGHT + SPEED_GRAPH_HEIGHT;
+        let sample_width = width / (SPEED_SAMPLES - 1) as f32;
+
+        draw_line(
+            top_left_corner.x,
+            graph_bottom,
+            top_left_corner.x + width,
+            graph_bottom,
+            2.,
+            Color::new(1.0, 1.0, 1.0, 0.4),
+        );
+
+        let points = self
+            .speed_samples
+            .iter()
+            .enumerate()
+            .map(|(i, speed)| {
+                vec2(
+                    top_left_corner.x + i as f32 * sample_width,
+                    graph_bottom - speed / max_speed * SPEED_GRAPH_HEIGHT,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for pair in points.windows(2) {
+            draw_line(
+                pair[0].x,
+                pair[0].y,
+                pair[1].x,
+                pair[1].y,
+                3.,
+                Color::new(0.4, 1.0, 0.5, 1.0),
+            );
+        }
+    }
+}
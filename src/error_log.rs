@@ -1,36 +1,63 @@
 use std::{fs::OpenOptions, io::Write};
 
 use circular_buffer::CircularBuffer;
+
 use macroquad::{
     color::Color,
+    input::mouse_wheel,
     math::{Rect, Vec2},
     shapes::draw_rectangle,
     text::{draw_text_ex, TextParams},
     time::get_time,
 };
 
+use crate::paths::get_game_path;
+
 const ERROR_HEIGHT: f32 = 120.0;
 const ERROR_PADDING: f32 = 10.0;
-const ERROR_MAX_COUNT: usize = 10;
+/// How many errors are kept. Older ones get removed.
+pub const ERROR_MAX_COUNT: usize = 10;
 
 const ERROR_ALPHA: f32 = 0.8;
 const ERROR_FONT_SIZE: u16 = 22;
 const ERROR_FONT_SIZE_F32: f32 = ERROR_FONT_SIZE as f32;
 
-const ERROR_START_DECAY: f64 = 3.0;
 const ERROR_DECAY_DURATION: f64 = 2.0;
 
-pub struct ErrorLogs(CircularBuffer<ERROR_MAX_COUNT, (f64, String)>);
+pub struct ErrorLogs {
+    errors: CircularBuffer<ERROR_MAX_COUNT, (f64, String)>,
+    headless: bool,
+    /// How many of the newest errors are scrolled past.
+    scroll_offset: usize,
+}
 
 impl ErrorLogs {
     pub fn new() -> Self {
-        Self(CircularBuffer::new())
+        Self {
+            errors: CircularBuffer::new(),
+            headless: false,
+            scroll_offset: 0,
+        }
+    }
+    /// Creates error logs that print the errors to stderr instead of displaying them.
+    ///
+    /// Used when there is no window.
+    pub fn headless() -> Self {
+        Self {
+            errors: CircularBuffer::new(),
+            headless: true,
+            scroll_offset: 0,
+        }
     }
     /// Adds a error to the log file aswell as displaying it inside the game.
     pub fn display_error(&mut self, error: String) {
         self.add_error(&error);
+        if self.headless {
+            eprintln!("{error}");
+            return;
+        }
         let time = get_time();
-        self.0.push_front((time, error));
+        self.errors.push_front((time, error));
     }
     /// Only adds a error to the log file.
     pub fn add_error(&self, error: &str) {
@@ -38,18 +65,59 @@ impl ErrorLogs {
             .create(true)
             .write(true)
             .append(true)
-            .open("error_log.txt")
+            .open(get_game_path("error_log.txt"))
         {
             let mut bytes = error.as_bytes().to_vec();
             bytes.extend("\n".as_bytes());
             let _ = log_file.write(&bytes);
         };
     }
-    /// Renders the errors to the screen.
-    pub fn render_errors(&self, top_left_corner: Vec2, width: f32) {
+    /// Renders the errors to the screen. Only `max_visible` errors are shown at once,
+    /// and scrolling while the mouse is over them shows the older ones.
+    ///
+    /// Errors start fading away after being shown for `display_time` seconds.
+    pub fn render_errors(
+        &mut self,
+        top_left_corner: Vec2,
+        width: f32,
+        max_visible: usize,
+        display_time: f64,
+        mouse_pos: Vec2,
+    ) {
         let time = get_time();
-        let start_decay_time = time - ERROR_START_DECAY;
-        for (i, (error_time, error)) in self.0.iter().enumerate() {
+        let start_decay_time = time - display_time;
+
+        // Errors that have fully faded away can't be scrolled to.
+        let shown_count = self
+            .errors
+            .iter()
+            .take_while(|(error_time, _)| start_decay_time - *error_time < ERROR_DECAY_DURATION)
+            .count();
+        let max_visible = max_visible.max(1);
+        let max_scroll_offset = shown_count.saturating_sub(max_visible);
+
+        let visible_height =
+            ERROR_PADDING + shown_count.min(max_visible) as f32 * (ERROR_HEIGHT + ERROR_PADDING);
+        let errors_rect = Rect::new(top_left_corner.x, top_left_corner.y, width, visible_height);
+
+        let (_, wheel_y) = mouse_wheel();
+        if wheel_y != 0. && errors_rect.contains(mouse_pos) {
+            if wheel_y < 0. {
+                self.scroll_offset += 1;
+            } else {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+        }
+        self.scroll_offset = self.scroll_offset.min(max_scroll_offset);
+
+        let visible_errors = self
+            .errors
+            .iter()
+            .take(shown_count)
+            .skip(self.scroll_offset)
+            .take(max_visible);
+
+        for (i, (error_time, error)) in visible_errors.enumerate() {
             let decay_value = (start_decay_time - *error_time).max(0.0) / ERROR_DECAY_DURATION;
             let alpha = (1.0 - decay_value).max(0.0);
 
@@ -0,0 +1,47 @@
+use macroquad::input::KeyCode;
+use nanoserde::{SerJson, SerJsonState};
+
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        &[$((KeyCode::$key, stringify!($key))),*]
+    };
+}
+
+/// Every key that can be bound, together with the name used for it in the settings file.
+#[rustfmt::skip]
+const KEY_NAMES: &[(KeyCode, &str)] = key_names!(
+    Space, Apostrophe, Comma, Minus, Period, Slash, Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7,
+    Key8, Key9, Semicolon, Equal, A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V,
+    W, X, Y, Z, LeftBracket, Backslash, RightBracket, GraveAccent, Escape, Enter, Tab, Backspace,
+    Insert, Delete, Right, Left, Down, Up, PageUp, PageDown, Home, End, CapsLock, ScrollLock,
+    NumLock, PrintScreen, Pause, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15,
+    F16, F17, F18, F19, F20, F21, F22, F23, F24, F25, Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8,
+    Kp9, KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual, LeftShift,
+    LeftControl, LeftAlt, LeftSuper, RightShift, RightControl, RightAlt, RightSuper, Menu,
+);
+
+/// Finds the key with the given name. The name is not case sensitive.
+pub fn key_code_from_name(name: &str) -> Option<KeyCode> {
+    return KEY_NAMES
+        .iter()
+        .find(|(_, key_name)| key_name.eq_ignore_ascii_case(name))
+        .map(|(key_code, _)| *key_code);
+}
+
+pub fn key_code_name(key_code: KeyCode) -> &'static str {
+    return KEY_NAMES
+        .iter()
+        .find(|(key, _)| *key == key_code)
+        .map(|(_, key_name)| *key_name)
+        .unwrap_or("Unknown");
+}
+
+/// A key that gets saved by its name in the settings file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyBinding(pub KeyCode);
+
+impl SerJson for KeyBinding {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        key_code_name(self.0).to_string().ser_json(d, s);
+    }
+}
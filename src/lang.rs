@@ -0,0 +1,80 @@
+use std::{collections::HashMap, fs, io::ErrorKind, path::PathBuf};
+
+use nanoserde::DeJson;
+
+use crate::error_log::ErrorLogs;
+
+/// The language the menu is written in, which doesn't need a file.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Menu text in another language, loaded from `lang/<code>.json` in the asset pack or the assets folder.
+///
+/// The file maps the English text to the translated text, like `{"Settings": "Einstellungen"}`.
+/// Anything that isn't in the file stays in English.
+pub struct Translations {
+    strings: HashMap<String, String>,
+}
+
+impl Translations {
+    pub fn english() -> Self {
+        Self {
+            strings: HashMap::new(),
+        }
+    }
+
+    pub fn load(
+        language: &str,
+        assets_path: PathBuf,
+        pack_path: Option<PathBuf>,
+        error_logs: &mut ErrorLogs,
+    ) -> Self {
+        if language.is_empty() || language == DEFAULT_LANGUAGE {
+            return Self::english();
+        }
+
+        let file_name = format!("{language}.json");
+        let paths = pack_path
+            .into_iter()
+            .chain([assets_path])
+            .map(|path| path.join("lang").join(&file_name));
+
+        for path in paths {
+            let string = match fs::read_to_string(&path) {
+                Ok(string) => string,
+                Err(err) => {
+                    if err.kind() == ErrorKind::NotFound {
+                        continue;
+                    }
+                    error_logs.display_error(format!(
+                        "Failed to read translations from \"{}\": {err}",
+                        path.to_string_lossy()
+                    ));
+                    return Self::english();
+                }
+            };
+
+            return match HashMap::<String, String>::deserialize_json(&string) {
+                Ok(strings) => Self { strings },
+                Err(err) => {
+                    error_logs.display_error(format!(
+                        "Failed to parse translations from \"{}\": {err}",
+                        path.to_string_lossy()
+                    ));
+                    Self::english()
+                }
+            };
+        }
+
+        error_logs.display_error(format!(
+            "No translations were found for the language \"{language}\". Using English instead."
+        ));
+        Self::english()
+    }
+
+    /// Returns the translated text, or the English text if there is no translation for it.
+    pub fn get<'a>(&'a self, text: &'a str) -> &'a str {
+        self.strings
+            .get(text)
+            .map_or(text, |string| string.as_str())
+    }
+}
@@ -1,33 +1,62 @@
 use std::{
+    env,
     f32::consts::PI,
-    thread,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    process, thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use assets::{find_pack, GameAssets};
-use ball::Ball;
+use assets::{find_pack, list_available_packs, GameAssets};
+use ball::{Ball, Walls, MAX_OBSTACLES};
 use circular_buffer::CircularBuffer;
 use conf::{Icon, Platform};
+use cycle::AssetCycler;
+use debug::DebugOverlay;
 use error_log::ErrorLogs;
-use macroquad::{audio::set_sound_volume, prelude::*, rand};
+use macroquad::{prelude::*, rand};
 use miniquad::*;
-use settings::{read_settings_file, write_settings_file, Settings};
-use sounds::{find_sounds, get_random_sounds};
-use textures::{find_texture, get_random_texture};
-use tutorial::{render_menu_tutorial, render_mouse_tutorial};
+use monitors::{find_window_monitor, list_monitors};
+use nanoserde::SerJson;
+use particles::Particles;
+use paths::{get_absolute_game_path, show_in_file_manager};
+use recording::{Recorder, Replay};
+use screenshot::save_screenshot;
+use settings::{
+    apply_env_overrides, read_settings_file, settings_from_json, write_settings_file,
+    BackgroundMode, MatchMode, Settings, SmoothingMode,
+};
+use sounds::{
+    find_sounds, get_random_sounds, list_available_sounds, load_sounds, SoundPack, UiSounds,
+};
+use textures::{
+    find_texture, get_random_texture, list_available_balls, load_ball_metadata, load_ball_texture,
+    BallMetadata,
+};
+use tutorial::{render_menu_hint, render_menu_tutorial, render_mouse_tutorial};
 use ui::{SettingsState, UiRenderer, MENU_SIZE};
 use window::{
-    get_window_position, set_mouse_cursor, set_swap_interval, set_window_position, set_window_size,
+    clipboard_get, clipboard_set, get_window_position, set_mouse_cursor, set_swap_interval,
+    set_window_position, set_window_size,
 };
+use window_region::{is_outside_rounded_box, set_window_clickthrough, CLICKTHROUGH_SUPPORTED};
 
 pub mod assets;
 pub mod ball;
+pub mod cycle;
+pub mod debug;
 pub mod error_log;
+pub mod keys;
+pub mod lang;
+pub mod monitors;
+pub mod particles;
+pub mod paths;
+pub mod recording;
+pub mod screenshot;
 pub mod settings;
 pub mod sounds;
 pub mod textures;
 pub mod tutorial;
 pub mod ui;
+pub mod window_region;
 
 include!(concat!(env!("OUT_DIR"), "/icon_data.rs"));
 
@@ -36,12 +65,59 @@ const FPS_LIMIT: u32 = 500;
 const BACKSPACES_BEFORE_MISSING: u8 = 7;
 
 const MOUSE_TUTORIAL_WAIT: f32 = 7.25;
-const WINDOW_DISTANCE_BEFORE_UNDERSTAND: f32 = 100.0;
 
 const MENU_TUTORIAL_WAIT: f32 = 7.;
 
+const IDLE_THROTTLE_WAIT: f32 = 5.;
+const IDLE_FPS: u32 = 10;
+
+/// How far the window moves from where it started in attract mode, in pixels.
+const ATTRACT_DISTANCE: f32 = 120.;
+/// How fast the window moves horizontally and vertically in attract mode, in radians per second.
+/// Different speeds make it trace a Lissajous curve instead of going back and forth on a line.
+const ATTRACT_SPEED: Vec2 = vec2(2.1, 2.9);
+
+/// When there's less deltatime than this left to simulate, it will be skipped.
+const MIN_REMAINING_DT: f32 = 0.00001;
+/// How many times the ball physics can step in one frame.
+const MAX_PHYSICS_STEPS: u32 = 10;
+
+/// How big the obstacles placed by clicking are.
+const OBSTACLE_RADIUS: f32 = 30.0;
+/// The inside of the obstacles.
+const OBSTACLE_COLOR: Color = Color::new(0.55, 0.55, 0.55, 1.0);
+/// Also used for the preview of where the next obstacle goes.
+const OBSTACLE_OUTLINE_COLOR: Color = Color::new(0.2, 0.2, 0.2, 1.0);
+
+/// How many frames at the start don't move the ball, so the big delta time from loading is skipped.
+const START_DELAY_FRAMES: u8 = 2;
+
+/// How much time each physics update simulates when using a fixed timestep.
+const FIXED_TIMESTEP: f32 = 1.0 / 240.0;
+/// How many fixed updates can run in one frame. Any time left after that is dropped so a slow frame can't snowball.
+const MAX_FIXED_UPDATES: u32 = 24;
+
+/// How much of the hit speed goes into the recoil of the window at a recoil strength of 1.
+const RECOIL_SCALE: f32 = 0.3;
+/// The recoil can never be faster or go further than this, so a hard hit doesn't throw the window across the screen.
+const MAX_RECOIL_SPEED: f32 = 1500.;
+const MAX_RECOIL_DISTANCE: f32 = 40.;
+/// How smoothly the window gets pulled back to where it was. It's critically damped so it doesn't keep shaking.
+const RECOIL_SMOOTHNESS: f32 = 0.115;
+
+/// How much of the FPS limiter wait is spent spinning instead of sleeping.
+/// Sleeping can take way longer than asked on some systems (around 15ms on Windows), which makes the frames uneven.
+const SPIN_WAIT_TIME: f64 = 0.002;
+
 pub fn window_conf() -> Conf {
-    let settings = read_settings_file().unwrap_or_default();
+    if env::args().skip(1).any(|arg| arg == "--list-assets") {
+        list_assets();
+        process::exit(0);
+    }
+
+    let mut settings = read_settings_file().unwrap_or_default();
+    // The errors are shown once the game has started.
+    let _ = apply_env_overrides(&mut settings);
 
     Conf {
         window_title: "Ball in a Box".to_string(),
@@ -49,9 +125,10 @@ pub fn window_conf() -> Conf {
         window_height: settings.box_height as i32,
         high_dpi: true,
         borderless: true,
-        fullscreen: false,
+        fullscreen: settings.fullscreen,
         window_resizable: false,
-        sample_count: 0,
+        // The window only gets created once, so this needs a restart to change.
+        sample_count: settings.msaa_samples as i32,
         icon: Some(Icon {
             small: ICON_SMALL,
             medium: ICON_MEDIUM,
@@ -65,15 +142,33 @@ pub fn window_conf() -> Conf {
     }
 }
 
+/// Prints all the balls, sounds and asset packs that can be loaded.
+fn list_assets() {
+    let mut error_logs = ErrorLogs::headless();
+
+    let categories = [
+        ("Balls", list_available_balls(&mut error_logs)),
+        ("Sounds", list_available_sounds(&mut error_logs)),
+        ("Asset packs", list_available_packs(&mut error_logs)),
+    ];
+
+    for (category, assets) in categories {
+        println!("{category} ({}):", assets.len());
+        for (name, path) in assets {
+            println!("    {name} ({})", path.to_string_lossy());
+        }
+    }
+}
+
 // https://theswissbay.ch/pdf/Gentoomen%20Library/Game%20Development/Programming/Game%20Programming%20Gems%204.pdf
 // 1.10
-pub fn smooth_vec2_critically_damped(
-    current: Vec2,
-    new: Vec2,
-    velocity: &mut Vec2,
+pub fn smooth_critically_damped(
+    current: f32,
+    new: f32,
+    velocity: &mut f32,
     smoothness: f32,
     delta_time: f32,
-) -> Vec2 {
+) -> f32 {
     if smoothness == 0.0 {
         if delta_time != 0.0 {
             *velocity = (new - current) / delta_time;
@@ -90,6 +185,143 @@ pub fn smooth_vec2_critically_damped(
     return new + (delta_pos + temp) * exp;
 }
 
+/// Spring that is allowed to overshoot the target a little before settling.
+pub fn smooth_spring(
+    current: f32,
+    new: f32,
+    velocity: &mut f32,
+    smoothness: f32,
+    delta_time: f32,
+) -> f32 {
+    if smoothness == 0.0 {
+        if delta_time != 0.0 {
+            *velocity = (new - current) / delta_time;
+        }
+        return new;
+    }
+
+    // Lower than 1 means it will overshoot.
+    const DAMPING_RATIO: f32 = 0.4;
+
+    let omega = 2.0 / smoothness;
+    let omega_squared = omega * omega;
+
+    // Implicit euler so that it stays stable even when the deltatime is big.
+    *velocity = (*velocity + delta_time * omega_squared * (new - current))
+        / (1.0
+            + 2.0 * DAMPING_RATIO * omega * delta_time
+            + omega_squared * delta_time * delta_time);
+    return current + *velocity * delta_time;
+}
+
+/// Moves a fixed fraction of the remaining distance every second. Has no momentum of its own.
+pub fn smooth_exponential(
+    current: f32,
+    new: f32,
+    velocity: &mut f32,
+    smoothness: f32,
+    delta_time: f32,
+) -> f32 {
+    if smoothness == 0.0 {
+        if delta_time != 0.0 {
+            *velocity = (new - current) / delta_time;
+        }
+        return new;
+    }
+
+    if delta_time == 0.0 {
+        return current;
+    }
+
+    let rate = 2.0 / smoothness;
+    let result = current + (new - current) * (1.0 - (-rate * delta_time).exp());
+    *velocity = (result - current) / delta_time;
+    return result;
+}
+
+/// Smooths each axis on its own with one of the smoothing functions, so they can have different smoothness.
+pub fn smooth_vec2(
+    smooth: fn(f32, f32, &mut f32, f32, f32) -> f32,
+    current: Vec2,
+    new: Vec2,
+    velocity: &mut Vec2,
+    smoothness: Vec2,
+    delta_time: f32,
+) -> Vec2 {
+    vec2(
+        smooth(current.x, new.x, &mut velocity.x, smoothness.x, delta_time),
+        smooth(current.y, new.y, &mut velocity.y, smoothness.y, delta_time),
+    )
+}
+
+/// Changes the settings and does everything needed for the new settings to take effect.
+pub async fn apply_settings(
+    mut new_settings: Settings,
+    settings: &mut Settings,
+    ball: &mut Ball,
+    ball_metadata: &mut BallMetadata,
+    game_assets: &mut GameAssets,
+    error_logs: &mut ErrorLogs,
+) {
+    let change_ball = new_settings.last_ball != settings.last_ball
+        || new_settings.max_texture_size != settings.max_texture_size
+        || new_settings.texture_filter != settings.texture_filter;
+    let change_sounds = new_settings.last_sounds != settings.last_sounds;
+    // The blend mode is part of the ball material, the translations come from the assets and the box textures
+    // get loaded with the texture filter, so the assets get loaded again.
+    let change_assets = new_settings.last_asset_pack != settings.last_asset_pack
+        || new_settings.ball_blend_mode != settings.ball_blend_mode
+        || new_settings.language != settings.language
+        || new_settings.texture_filter != settings.texture_filter;
+    // The thickness or ball might have gotten bigger than what the box size was picked for.
+    new_settings.validate_box_size();
+    *settings = new_settings;
+    write_settings_file(settings);
+    ball.set_hit_volume(settings.audio_volume);
+    if !settings.fullscreen {
+        set_window_size(settings.box_width, settings.box_height);
+    }
+    set_swap_interval(if settings.vsync { 1 } else { 0 });
+    if change_ball {
+        if let Some((ball_name, texture)) = find_texture(&settings.last_ball, settings, error_logs)
+        {
+            ball.texture = texture;
+            *ball_metadata = load_ball_metadata(&ball_name, error_logs);
+        }
+    }
+
+    if change_sounds {
+        if let Some((_, sounds)) =
+            find_sounds(&settings.last_sounds, settings.match_mode, error_logs).await
+        {
+            ball.set_sounds(sounds);
+        }
+    }
+
+    if change_assets {
+        let pack_path = if !settings.last_asset_pack.is_empty() {
+            if let Some((_, pack_path)) =
+                find_pack(&settings.last_asset_pack, settings.match_mode, error_logs)
+            {
+                Some(pack_path)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        *game_assets = GameAssets::new(
+            pack_path,
+            game_assets.missing_texture.clone(),
+            settings.ball_blend_mode,
+            settings.texture_filter,
+            &settings.language,
+            error_logs,
+        )
+    }
+}
+
 pub trait FromTuple {
     fn from_i32_tuple(tuple: (i32, i32)) -> Self;
 }
@@ -100,19 +332,117 @@ impl FromTuple for Vec2 {
     }
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
-    {
-        let start = SystemTime::now();
-        let seed = start
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_else(|err| err.duration())
-            .as_nanos() as u64;
+/// Fills the area with copies of the texture at its native resolution.
+/// The tiles on the right and bottom edges are cut off so nothing is drawn outside the area.
+pub fn draw_texture_tiled(texture: &Texture2D, top_left: Vec2, area_size: Vec2, color: Color) {
+    // Really small textures would need thousands of tiles, so they get scaled up a bit.
+    const MIN_TILE_SIZE: f32 = 16.;
+
+    // The camera shows 2 units for every pixel on the screen.
+    let texture_size = texture.size();
+    let tile_size = (texture_size * 2.).max(Vec2::splat(MIN_TILE_SIZE));
+
+    let mut y = 0.;
+    while y < area_size.y {
+        let height = tile_size.y.min(area_size.y - y);
+
+        let mut x = 0.;
+        while x < area_size.x {
+            let width = tile_size.x.min(area_size.x - x);
+
+            draw_texture_ex(
+                texture,
+                top_left.x + x,
+                top_left.y + y,
+                color,
+                DrawTextureParams {
+                    dest_size: Some(vec2(width, height)),
+                    source: Some(Rect::new(
+                        0.,
+                        0.,
+                        texture_size.x * width / tile_size.x,
+                        texture_size.y * height / tile_size.y,
+                    )),
+                    ..Default::default()
+                },
+            );
+
+            x += tile_size.x;
+        }
+
+        y += tile_size.y;
+    }
+}
+
+/// Draws a red dot, to show that the window movement is being recorded.
+pub fn draw_recording_indicator(center: Vec2) {
+    draw_circle(center.x, center.y, 12., Color::new(0.9, 0.1, 0.1, 0.8));
+}
+
+/// Draws an arrow pointing up, to show that gravity has been flipped.
+pub fn draw_gravity_flipped_indicator(top_center: Vec2) {
+    const ARROW_SIZE: f32 = 30.;
+    let color = Color::new(1., 1., 1., 0.6);
+
+    draw_triangle(
+        top_center,
+        top_center + vec2(-ARROW_SIZE, ARROW_SIZE),
+        top_center + vec2(ARROW_SIZE, ARROW_SIZE),
+        color,
+    );
+    draw_rectangle(
+        top_center.x - ARROW_SIZE * 0.3,
+        top_center.y + ARROW_SIZE,
+        ARROW_SIZE * 0.6,
+        ARROW_SIZE,
+        color,
+    );
+}
+
+/// Waits for the duration by sleeping for most of it and spinning for the rest, so it doesn't overshoot.
+pub fn wait_precise(duration: f64) {
+    let end = Instant::now() + Duration::from_secs_f64(duration);
+
+    if duration > SPIN_WAIT_TIME {
+        thread::sleep(Duration::from_secs_f64(duration - SPIN_WAIT_TIME));
+    }
+
+    while Instant::now() < end {
+        std::hint::spin_loop();
+    }
+}
+
+/// The settings the ball uses, with the suggestions from the ball's metadata file and the asset pack.
+pub fn merge_ball_settings(
+    settings: &Settings,
+    ball_metadata: &BallMetadata,
+    game_assets: &GameAssets,
+) -> Settings {
+    let mut ball_settings = ball_metadata.apply_to(settings);
+    game_assets.pack_settings.apply_to(&mut ball_settings);
+    return ball_settings;
+}
 
-        rand::srand(seed);
+/// Puts the ball in the middle of the box, or where the cursor is if `spawn_at_cursor` is on.
+pub fn spawn_ball(ball: &mut Ball, settings: &Settings, box_size: Vec2) {
+    if settings.spawn_at_cursor {
+        let local_mouse_pos = Vec2::from_i32_tuple(window::get_screen_mouse_position())
+            - Vec2::from_i32_tuple(get_window_position());
+        ball.spawn_at(local_mouse_pos * 2. - box_size, settings, box_size);
+    } else {
+        ball.spawn_at(Vec2::ZERO, settings, box_size);
     }
+}
 
+#[macroquad::main(window_conf)]
+async fn main() {
     let mut error_logs = ErrorLogs::new();
+    let mut debug_overlay = DebugOverlay::new();
+    let mut particles = Particles::new();
+    let mut ball_cycler = AssetCycler::new();
+    let mut sounds_cycler = AssetCycler::new();
+
+    let mut paused = false;
 
     let mut settings = read_settings_file().unwrap_or_else(|| {
         let settings = Settings::default();
@@ -120,6 +450,19 @@ async fn main() {
         settings
     });
 
+    for error in apply_env_overrides(&mut settings) {
+        error_logs.display_error(error);
+    }
+
+    // A seed in the settings makes the random ball and sounds the same every time.
+    let seed = settings.random_seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|err| err.duration())
+            .as_nanos() as u64
+    });
+    rand::srand(seed);
+
     let missing_texture = Texture2D::from_rgba8(
         2,
         2,
@@ -130,7 +473,11 @@ async fn main() {
     missing_texture.set_filter(macroquad::texture::FilterMode::Nearest);
 
     let pack_path = if !settings.last_asset_pack.is_empty() {
-        if let Some((_, pack_path)) = find_pack(&settings.last_asset_pack, &mut error_logs) {
+        if let Some((_, pack_path)) = find_pack(
+            &settings.last_asset_pack,
+            settings.match_mode,
+            &mut error_logs,
+        ) {
             Some(pack_path)
         } else {
             None
@@ -139,37 +486,71 @@ async fn main() {
         None
     };
 
-    let mut game_assets = GameAssets::new(pack_path, missing_texture, &mut error_logs);
+    let mut game_assets = GameAssets::new(
+        pack_path,
+        missing_texture,
+        settings.ball_blend_mode,
+        settings.texture_filter,
+        &settings.language,
+        &mut error_logs,
+    );
+
+    let ui_sounds = UiSounds::load(&mut error_logs).await;
+
+    let mut ball_metadata;
 
     let mut ball = {
-        let option_sounds = find_sounds(&settings.last_sounds, &mut error_logs).await;
+        let option_sounds =
+            find_sounds(&settings.last_sounds, settings.match_mode, &mut error_logs).await;
 
         let sounds = if let Some(sounds) = option_sounds {
             sounds
         } else {
             get_random_sounds(&mut error_logs)
                 .await
-                .unwrap_or_else(|| (settings.last_sounds.clone(), Vec::new()))
+                .unwrap_or_else(|| (settings.last_sounds.clone(), SoundPack::default()))
         };
 
-        Ball::new(
-            find_texture(&settings.last_ball, &mut error_logs)
-                .unwrap_or_else(|| {
-                    get_random_texture(&mut error_logs).unwrap_or_else(|| {
-                        (
-                            settings.last_ball.clone(),
-                            game_assets.missing_texture.clone(),
-                        )
-                    })
+        let (ball_name, texture) = find_texture(&settings.last_ball, &settings, &mut error_logs)
+            .unwrap_or_else(|| {
+                get_random_texture(&settings, &mut error_logs).unwrap_or_else(|| {
+                    (
+                        settings.last_ball.clone(),
+                        game_assets.missing_texture.clone(),
+                    )
                 })
-                .1,
+            });
+
+        ball_metadata = load_ball_metadata(&ball_name, &mut error_logs);
+
+        Ball::new(
+            texture,
             settings.ball_radius as f32,
+            settings.ball_start_rotation,
             sounds.1,
         )
     };
 
+    let mut ball_settings = merge_ball_settings(&settings, &ball_metadata, &game_assets);
+    let mut ball_settings_outdated = false;
+
     let mut box_size = vec2(settings.box_width as f32, settings.box_height as f32);
 
+    let mut window_clickthrough = false;
+    let mut warned_about_clickthrough = false;
+    let mut warned_about_ball_reset = false;
+
+    let monitors = list_monitors();
+
+    if let Some(monitor_index) = settings.start_monitor {
+        if let Some(monitor) = monitors.get(monitor_index as usize) {
+            let new_pos = monitor.get_centered_position(box_size);
+            set_window_position(new_pos.x as i32, new_pos.y as i32);
+        }
+    }
+
+    spawn_ball(&mut ball, &settings, box_size);
+
     set_camera(&Camera2D {
         zoom: vec2(1. / box_size.x, 1. / box_size.y),
         ..Default::default()
@@ -181,10 +562,25 @@ async fn main() {
     let mut ui_renderer = UiRenderer::new().await;
 
     let mut do_drag = false;
+    let mut is_grabbing_ball = false;
+    // Circles inside the box that the ball bounces off, as a position in the box and a radius.
+    let mut obstacles: Vec<(Vec2, f32)> = Vec::new();
+    // While placing obstacles, clicking in the box places or removes one instead of moving the window.
+    let mut placing_obstacles = false;
+
+    let mut is_fullscreen = settings.fullscreen;
+    // Where the window was before going into fullscreen, so it can be put back.
+    let mut windowed_position: Option<(i32, i32)> = None;
     let mut is_in_settings = false;
-    let mut settings_state = SettingsState::Closed;
+    let mut settings_state = if settings.open_menu_on_start {
+        SettingsState::Open
+    } else {
+        SettingsState::Closed
+    };
 
     let mut editing_settings = settings.clone();
+    // The settings from before the last apply, so it can be undone.
+    let mut settings_before_apply: Option<Settings> = None;
 
     let mut mouse_offset: Option<Vec2> = None;
     let mut mouse_deltas: CircularBuffer<10, Vec2> = CircularBuffer::new();
@@ -192,10 +588,23 @@ async fn main() {
     let mut old_visual_window_position = Vec2::ZERO;
     let mut old_internal_window_position = Vec2::ZERO;
     let mut window_velocity = Vec2::ZERO;
+    // The window velocity the ball reacts to, after going through the velocity filter.
+    let mut filtered_window_velocity = Vec2::ZERO;
 
     let mut frames_after_start: u8 = 0;
+    // How many frames the ball waits before it starts moving. Can be turned off for when the timing matters.
+    let start_delay_frames = if env::args().skip(1).any(|arg| arg == "--no-start-delay") {
+        0
+    } else {
+        START_DELAY_FRAMES
+    };
     let mut prev_render_time = get_time();
-    let mut time_since_start = 0.;
+    // How long the game has been running with the menu closed, since the tutorials would be hidden behind it.
+    let mut tutorial_time = 0.;
+
+    // Time that hasn't been simulated yet when using a fixed timestep, and where the ball was before the last update.
+    let mut fixed_time_accumulator = 0.0_f32;
+    let mut previous_fixed_position = ball.position;
 
     let mut total_window_distance = 0.;
     let mut time_of_understanding_move = if settings.understands_moving {
@@ -213,6 +622,22 @@ async fn main() {
     let mut clicked_mouse_position = Vec2::ZERO;
     let mut moved_during_hold = false;
 
+    let mut idle_time = 0.;
+    // How long there hasn't been any mouse or keyboard input, for attract mode.
+    let mut input_idle_time = 0.;
+    // The window position attract mode started at and how long it has been going, if it's active.
+    let mut attract_state: Option<(Vec2, f32)> = None;
+    // The window position from before the recoil, and how far and fast it's currently pushed away from it.
+    let mut recoil_state: Option<(Vec2, Vec2, Vec2)> = None;
+    let mut recorder: Option<Recorder> = None;
+    let mut replay: Option<Replay> = None;
+    // If the replay was dragging the window last frame, so a new drag can be started like a real one.
+    let mut replay_was_dragging = false;
+    let mut last_mouse_position = Vec2::ZERO;
+    // How long the mouse hasn't moved, for hiding the cursor.
+    let mut mouse_idle_time = 0.;
+    let mut cursor_hidden = false;
+
     loop {
         clear_background(DARKGRAY);
 
@@ -221,14 +646,31 @@ async fn main() {
 
         // First frame loads everything, second frame will have a high delta time because of loading a lot the previous frame.
         // Delay the actual delta time until after that so the user can see the ball spawn in middle and bounce.
-        if frames_after_start >= 2 {
-            delta_time = real_delta_time * settings.speed_mul
+        if frames_after_start >= start_delay_frames {
+            // Holding the slowdown key slows everything down on top of the game speed.
+            let slowdown =
+                if !ui_renderer.is_typing() && is_key_down(settings.keybindings.slowdown.0) {
+                    settings.slowdown_factor
+                } else {
+                    1.0
+                };
+            // A minimized window or a hitch might not get any frames, so the first frame after would have a huge delta time.
+            // Capping it makes the ball continue where it was instead of jumping or flying through the walls.
+            // It's never capped below the FPS limit though, or the game would run slower than it should.
+            let max_delta_time = if settings.max_fps < FPS_LIMIT {
+                settings.max_delta_time.max(1. / settings.max_fps as f32)
+            } else {
+                settings.max_delta_time
+            };
+            delta_time = real_delta_time.min(max_delta_time) * settings.speed_mul * slowdown
         } else {
             frames_after_start += 1;
             delta_time = 0.0
         }
 
-        time_since_start += delta_time;
+        if !settings_state.is_open() {
+            tutorial_time += delta_time;
+        }
 
         let box_thickness = settings.box_thickness as f32;
 
@@ -258,10 +700,27 @@ async fn main() {
         last_left_button_is_down = left_button_is_down;
         last_right_button_is_down = right_button_is_down;
 
-        let open_menu = button_pressed && last_click > 0.0 || is_key_pressed(KeyCode::Escape);
+        let open_menu =
+            button_pressed && last_click > 0.0 || is_key_pressed(settings.keybindings.open_menu.0);
 
         let current_mouse_position = Vec2::from_i32_tuple(window::get_screen_mouse_position());
 
+        let had_input = current_mouse_position != last_mouse_position
+            || button_is_down
+            || !get_keys_down().is_empty();
+
+        if had_input {
+            input_idle_time = 0.;
+        } else {
+            input_idle_time += real_delta_time;
+        }
+
+        if current_mouse_position != last_mouse_position {
+            mouse_idle_time = 0.;
+        } else {
+            mouse_idle_time += real_delta_time;
+        }
+
         if button_pressed {
             last_click = 0.4;
             clicked_mouse_position = current_mouse_position;
@@ -320,34 +779,68 @@ async fn main() {
 
             text_input.push(character.to_ascii_lowercase());
 
-            if let Some((ball_name, texture)) = find_texture(&text_input, &mut error_logs) {
+            let mut selected_something = false;
+
+            if let Some((ball_name, texture)) =
+                find_texture(&text_input, &settings, &mut error_logs)
+            {
                 ball.texture = texture;
+                ball_metadata = load_ball_metadata(&ball_name, &mut error_logs);
+                ball_settings_outdated = true;
                 settings.last_ball = ball_name.clone();
                 editing_settings.last_ball = ball_name;
                 write_settings_file(&settings);
+                selected_something = true;
             }
 
-            if let Some((sounds_name, sounds)) = find_sounds(&text_input, &mut error_logs).await {
-                ball.sounds = sounds.clone();
+            if let Some((sounds_name, sounds)) =
+                find_sounds(&text_input, settings.match_mode, &mut error_logs).await
+            {
+                ball.set_sounds(sounds);
                 settings.last_sounds = sounds_name.clone();
                 editing_settings.last_sounds = sounds_name;
                 write_settings_file(&settings);
+                selected_something = true;
             }
 
-            if let Some((pack_name, pack_path)) = find_pack(&text_input, &mut error_logs) {
+            if let Some((pack_name, pack_path)) =
+                find_pack(&text_input, settings.match_mode, &mut error_logs)
+            {
                 settings.last_asset_pack = pack_name.clone();
                 editing_settings.last_asset_pack = pack_name;
                 write_settings_file(&settings);
                 game_assets = GameAssets::new(
                     Some(pack_path),
                     game_assets.missing_texture,
+                    settings.ball_blend_mode,
+                    settings.texture_filter,
+                    &settings.language,
                     &mut error_logs,
-                )
-            } else if text_input.ends_with("none") && !settings.last_asset_pack.is_empty() {
+                );
+                ball_settings_outdated = true;
+                selected_something = true;
+            } else if settings.match_mode.matches(&text_input, "none")
+                && !settings.last_asset_pack.is_empty()
+            {
                 settings.last_asset_pack = String::new();
                 editing_settings.last_asset_pack = String::new();
                 write_settings_file(&settings);
-                game_assets = GameAssets::new(None, game_assets.missing_texture, &mut error_logs)
+                game_assets = GameAssets::new(
+                    None,
+                    game_assets.missing_texture,
+                    settings.ball_blend_mode,
+                    settings.texture_filter,
+                    &settings.language,
+                    &mut error_logs,
+                );
+                ball_settings_outdated = true;
+                selected_something = true;
+            }
+
+            // With suffix matching, typing more characters stops the name from matching.
+            // The other modes would keep matching the same name, so the text has to start over.
+            if selected_something && settings.match_mode != MatchMode::Suffix {
+                text_input.clear();
             }
         }
         if is_key_pressed(KeyCode::Backspace) {
@@ -365,12 +858,50 @@ async fn main() {
         };
 
         // Don't move window if overlapping with menu.
-        if button_pressed && (!is_menu_open || !hovering_menu) {
+        // Clicking on the ball grabs it instead of moving the window.
+        let obstacle_click =
+            placing_obstacles && button_pressed && !do_drag && (!is_menu_open || !hovering_menu);
+
+        if obstacle_click {
+            let position = local_mouse_pos * 2. - box_size;
+            if let Some(index) = obstacles.iter().position(|(obstacle_position, radius)| {
+                obstacle_position.distance(position) <= *radius
+            }) {
+                obstacles.remove(index);
+            } else if obstacles.len() < MAX_OBSTACLES {
+                obstacles.push((position, OBSTACLE_RADIUS));
+            }
+        }
+
+        if settings.grab_ball
+            && button_pressed
+            && !do_drag
+            && !obstacle_click
+            && (!is_menu_open || !hovering_menu)
+            && (local_mouse_pos * 2. - box_size).distance(ball.position) <= ball.radius
+        {
+            is_grabbing_ball = true;
+        } else if !button_is_down {
+            is_grabbing_ball = false;
+        }
+
+        // The window can't be moved while it's fullscreen.
+        if button_pressed
+            && (!is_menu_open || !hovering_menu)
+            && !is_fullscreen
+            && !is_grabbing_ball
+            && !obstacle_click
+        {
             do_drag = true
         } else if button_released && moved_during_hold {
             do_drag = false
         }
 
+        // With the window locked, clicks can still open the menu but never move the window.
+        if settings.lock_window {
+            do_drag = false;
+        }
+
         if (!get_keys_pressed().is_empty() && !is_key_pressed(KeyCode::Backspace)) || do_drag {
             times_clicked_backspace = 0
         }
@@ -381,37 +912,120 @@ async fn main() {
 
         let mouse_offset_was_some = mouse_offset.is_some();
 
+        // Dragging the window takes over from a replay.
+        if do_drag
+            || settings.lock_window
+            || replay.as_ref().is_some_and(|replay| replay.is_finished())
+        {
+            replay = None;
+        }
+
+        let replay_target = replay
+            .as_mut()
+            .and_then(|replay| replay.next_target(|| Vec2::from_i32_tuple(get_window_position())));
+
+        if replay_target.is_some() && !replay_was_dragging {
+            window_velocity = Vec2::ZERO;
+            old_internal_window_position = Vec2::from_i32_tuple(get_window_position());
+            old_visual_window_position = old_internal_window_position;
+        }
+        replay_was_dragging = replay_target.is_some();
+
+        // Attract mode moves the window by itself after there hasn't been any input for a while.
+        // Any input stops it right away since the idle time gets reset.
+        let is_attracting = settings.attract_mode
+            && input_idle_time > settings.attract_delay
+            && !do_drag
+            && !is_menu_open
+            && !is_fullscreen
+            && !is_grabbing_ball
+            && !settings.lock_window
+            && replay.is_none();
+
+        let attract_target = if is_attracting {
+            let (attract_start, attract_time) = attract_state.get_or_insert_with(|| {
+                let window_position = Vec2::from_i32_tuple(get_window_position());
+                window_velocity = Vec2::ZERO;
+                old_internal_window_position = window_position;
+                old_visual_window_position = window_position;
+                (window_position, 0.)
+            });
+            *attract_time += delta_time;
+
+            let attract_offset = vec2(
+                (*attract_time * ATTRACT_SPEED.x).sin(),
+                (*attract_time * ATTRACT_SPEED.y).sin(),
+            ) * ATTRACT_DISTANCE;
+            Some(*attract_start + attract_offset)
+        } else {
+            attract_state = None;
+            None
+        };
+
+        // Replays and attract mode move the window towards a target instead of the mouse.
+        let window_target = replay_target.or(attract_target);
+        let mut drag_target = None;
+
         // Update internal / visual window position and get delta position of window.
-        let visual_delta_pos = if do_drag {
-            let mouse_offset = match mouse_offset {
-                Some(mouse_offset) => mouse_offset,
-                None => {
-                    mouse_offset = Some(-local_mouse_pos);
-                    window_velocity = Vec2::ZERO;
-                    old_internal_window_position = current_mouse_position - local_mouse_pos;
-                    old_visual_window_position = old_internal_window_position;
-                    -local_mouse_pos
-                }
+        let visual_delta_pos = if do_drag || window_target.is_some() {
+            let new_pos = if let Some(window_target) = window_target {
+                window_target
+            } else {
+                let mouse_offset = match mouse_offset {
+                    Some(mouse_offset) => mouse_offset,
+                    None => {
+                        mouse_offset = Some(-local_mouse_pos);
+                        window_velocity = Vec2::ZERO;
+                        old_internal_window_position = current_mouse_position - local_mouse_pos;
+                        old_visual_window_position = old_internal_window_position;
+                        -local_mouse_pos
+                    }
+                };
+
+                drag_target = Some(current_mouse_position + mouse_offset);
+                current_mouse_position + mouse_offset
+            };
+            let smooth = match settings.smoothing_mode {
+                SmoothingMode::Critical => smooth_critically_damped,
+                SmoothingMode::Spring => smooth_spring,
+                SmoothingMode::Exponential => smooth_exponential,
             };
 
-            let new_pos = current_mouse_position + mouse_offset;
-            let new_internal_window_pos = smooth_vec2_critically_damped(
+            let new_internal_window_pos = smooth_vec2(
+                smooth,
                 old_internal_window_position,
                 new_pos,
                 &mut window_velocity,
-                settings.box_weight,
+                vec2(settings.box_weight_x, settings.box_weight_y),
                 delta_time,
             );
 
-            let new_visual_window_pos = if settings.hide_smoothing {
+            let mut new_visual_window_pos = if settings.hide_smoothing {
                 new_pos
             } else {
                 new_internal_window_pos
             };
 
+            // Limit how far the window can move each frame. The window keeps moving towards the target
+            // on the next frames, so the part that was cut off isn't lost.
+            if settings.max_window_speed > 0. {
+                let max_distance = settings.max_window_speed * delta_time;
+                new_visual_window_pos = old_visual_window_position
+                    + (new_visual_window_pos - old_visual_window_position)
+                        .clamp_length_max(max_distance);
+
+                if !settings.hide_smoothing {
+                    window_velocity = window_velocity.clamp_length_max(settings.max_window_speed);
+                }
+            }
+
             let visual_delta_pos = new_visual_window_pos - old_visual_window_position;
 
-            old_internal_window_position = new_internal_window_pos;
+            old_internal_window_position = if settings.hide_smoothing {
+                new_internal_window_pos
+            } else {
+                new_visual_window_pos
+            };
             old_visual_window_position = new_visual_window_pos;
             -visual_delta_pos
         } else {
@@ -420,6 +1034,10 @@ async fn main() {
             Vec2::ZERO
         };
 
+        if let Some(recorder) = &mut recorder {
+            recorder.add_frame(drag_target);
+        }
+
         // Position window and handle delay frames
         let delay_frames = settings.delay_frames as usize;
         if delay_frames != 0 {
@@ -431,7 +1049,7 @@ async fn main() {
             }
         }
 
-        if do_drag {
+        if do_drag || window_target.is_some() {
             let mut new_pos = old_visual_window_position;
 
             for delta in mouse_deltas.iter() {
@@ -460,7 +1078,7 @@ async fn main() {
         }
 
         // Adjust velocity
-        if settings.quick_turn {
+        if settings.quick_turn && window_target.is_none() {
             let offset_mouse_pos = current_mouse_position + mouse_offset.unwrap_or(Vec2::ZERO);
 
             if offset_mouse_pos.x > old_visual_window_position.x {
@@ -482,54 +1100,262 @@ async fn main() {
             visual_delta_pos / delta_time
         };
 
-        // Ball physics
-        let mut remaining_dt = delta_time;
+        // Smooths out the window movement the ball feels, so jerky mouse movement doesn't shake it around.
+        // The filter is a time constant in seconds, so it acts the same at any FPS.
+        if settings.velocity_filter > 0. {
+            let blend = 1. - (-delta_time / settings.velocity_filter).exp();
+            filtered_window_velocity = filtered_window_velocity.lerp(visual_window_velocity, blend);
+        } else {
+            filtered_window_velocity = visual_window_velocity;
+        }
 
-        let mut steps = 0;
-        let mut wall_hits = [0, 0];
+        // Merging them every frame would be wasteful, so it only happens when the settings, ball or asset pack change.
+        if ball_settings_outdated {
+            ball_settings = merge_ball_settings(&settings, &ball_metadata, &game_assets);
+            ball_settings_outdated = false;
+        }
+        ball.radius = ball_settings.ball_radius as f32;
+
+        // A heavier ball gets flung less by the window.
+        let mass_factor = 1. - ball_settings.ball_weight * ball_settings.weight_throw_influence;
+        let throw_strength = settings.throw_strength * mass_factor;
+
+        // With free roam the ball bounces against the edges of the monitor the box is on instead of the box walls.
+        // If the monitors can't be found it will use the box walls like normal.
+        let free_roam_monitor = if settings.free_roam {
+            let window_position = Vec2::from_i32_tuple(get_window_position());
+            find_window_monitor(&monitors, window_position, box_size)
+                .map(|monitor| (window_position, monitor))
+        } else {
+            None
+        };
+
+        let (walls, visual_box_velocity, smoothed_box_velocity) =
+            if let Some((window_position, monitor)) = free_roam_monitor {
+                // The screen doesn't move, so the ball should move exactly opposite of the box to stay in place.
+                (
+                    Walls {
+                        center: (monitor.position + monitor.size / 2. - window_position) * 2.
+                            - box_size,
+                        half_size: monitor.size,
+                        offset: 0.,
+                        corner_radius: 0.,
+                    },
+                    visual_window_velocity * 2.,
+                    Vec2::ZERO,
+                )
+            } else {
+                (
+                    Walls::from_box(box_size, &settings),
+                    filtered_window_velocity * throw_strength,
+                    -window_velocity * throw_strength,
+                )
+            };
 
-        while remaining_dt > 0.00001 && steps < 10 {
-            steps += 1;
-            remaining_dt = ball.step(
-                remaining_dt,
+        if is_grabbing_ball {
+            ball.move_grabbed(
+                local_mouse_pos * 2. - box_size,
                 &settings,
-                visual_window_velocity * 2.,
-                -window_velocity * 2.,
-                &mut wall_hits,
                 box_size,
+                delta_time,
             );
         }
 
+        // Ball physics
+        let remaining_dt = if paused || is_grabbing_ball {
+            0.
+        } else {
+            delta_time
+        };
+
+        let cursor_position = if settings.cursor_gravity {
+            Some(local_mouse_pos * 2. - box_size)
+        } else {
+            None
+        };
+
+        let mut steps = 0;
+        let mut wall_hits = [0, 0];
+        let mut walls_contacted = [false; 4];
+        let mut impacts = Vec::new();
+        let mut ball_was_reset = false;
+
+        let mut simulate = |ball: &mut Ball, mut remaining_dt: f32| {
+            let mut update_steps = 0;
+            while remaining_dt > MIN_REMAINING_DT && update_steps < MAX_PHYSICS_STEPS {
+                update_steps += 1;
+                remaining_dt = ball.step(
+                    remaining_dt,
+                    &ball_settings,
+                    visual_box_velocity,
+                    smoothed_box_velocity,
+                    &mut wall_hits,
+                    &mut walls_contacted,
+                    &mut impacts,
+                    &walls,
+                    &obstacles,
+                    cursor_position,
+                    &mut ball_was_reset,
+                );
+            }
+            steps += update_steps;
+        };
+
+        if settings.fixed_timestep && !paused && !is_grabbing_ball {
+            fixed_time_accumulator += remaining_dt;
+
+            let mut fixed_updates = 0;
+            while fixed_time_accumulator >= FIXED_TIMESTEP && fixed_updates < MAX_FIXED_UPDATES {
+                fixed_updates += 1;
+                previous_fixed_position = ball.position;
+                simulate(&mut ball, FIXED_TIMESTEP);
+                fixed_time_accumulator -= FIXED_TIMESTEP;
+            }
+
+            if fixed_updates == MAX_FIXED_UPDATES {
+                fixed_time_accumulator = 0.;
+            }
+        } else {
+            simulate(&mut ball, remaining_dt);
+            fixed_time_accumulator = 0.;
+            previous_fixed_position = ball.position;
+        }
+
+        ball.update_roll_sound(&ball_settings, !paused && !is_grabbing_ball);
+
+        if ball_was_reset {
+            previous_fixed_position = ball.position;
+
+            // It could happen every frame with the same settings, so it's only shown once.
+            if !warned_about_ball_reset {
+                error_logs.display_error(
+                    "The ball physics broke, so the ball was put back in the middle of the box. Some of the settings might be too extreme."
+                        .to_string(),
+                );
+                warned_about_ball_reset = true;
+            }
+        }
+
+        // Hard hits push the window away a bit, and then it springs back to where it was.
+        // Only while nothing else is moving the window, and the ball doesn't feel the recoil, so it can't build up.
+        let can_recoil = settings.recoil_strength > 0.
+            && !do_drag
+            && window_target.is_none()
+            && mouse_deltas.is_empty()
+            && !is_fullscreen
+            && !settings.lock_window
+            && !settings.free_roam;
+
+        if can_recoil {
+            let recoil_impulse = impacts
+                .iter()
+                .filter(|impact| impact.speed > settings.min_hit_speed)
+                .map(|impact| -impact.direction * impact.speed)
+                .sum::<Vec2>()
+                * settings.recoil_strength
+                * RECOIL_SCALE;
+
+            if recoil_impulse != Vec2::ZERO || recoil_state.is_some() {
+                let (rest_position, offset, velocity) = recoil_state.get_or_insert_with(|| {
+                    (
+                        Vec2::from_i32_tuple(get_window_position()),
+                        Vec2::ZERO,
+                        Vec2::ZERO,
+                    )
+                });
+
+                *velocity = (*velocity + recoil_impulse).clamp_length_max(MAX_RECOIL_SPEED);
+                // Uses the same damping as the window smoothing so it stays stable at low FPS.
+                *offset = vec2(
+                    smooth_critically_damped(
+                        offset.x,
+                        0.,
+                        &mut velocity.x,
+                        RECOIL_SMOOTHNESS,
+                        real_delta_time,
+                    ),
+                    smooth_critically_damped(
+                        offset.y,
+                        0.,
+                        &mut velocity.y,
+                        RECOIL_SMOOTHNESS,
+                        real_delta_time,
+                    ),
+                )
+                .clamp_length_max(MAX_RECOIL_DISTANCE);
+
+                let new_pos = *rest_position + *offset;
+                set_window_position(new_pos.x.round() as i32, new_pos.y.round() as i32);
+
+                if offset.length() < 0.5 && velocity.length() < 5. {
+                    set_window_position(rest_position.x as i32, rest_position.y as i32);
+                    recoil_state = None;
+                }
+            }
+        } else {
+            recoil_state = None;
+        }
+
+        particles.spawn_bursts(&impacts, &settings);
+        particles.update(remaining_dt, &settings);
+
+        debug_overlay.add_line(format!("FPS: {}", get_fps()));
+        debug_overlay.add_line(format!("Physics steps: {steps}"));
+        debug_overlay.add_walls_contacted(&walls_contacted);
+        debug_overlay.add_speed_sample(ball.velocity.length());
+
         // Update distance and check if it has traveled far enough for the person to understand the tutorial.
         // This will fail if the person accidentally does a "click-to-drag" and is confused as to why the window is now following the cursor.
         // Idk how I would go about detecting that tho.
         total_window_distance += visual_delta_pos.length();
 
         if time_of_understanding_move.is_none()
-            && total_window_distance > WINDOW_DISTANCE_BEFORE_UNDERSTAND
+            && total_window_distance > settings.tutorial_move_distance
         {
             settings.understands_moving = true;
             editing_settings.understands_moving = true;
             write_settings_file(&settings);
-            time_of_understanding_move = Some(time_since_start);
+            time_of_understanding_move = Some(tutorial_time);
         }
 
         // Render
 
         // Background
-        draw_texture_ex(
-            &game_assets.box_background_texture,
-            -box_size.x + box_thickness,
-            -box_size.y + box_thickness,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(
+        match settings.background_mode {
+            _ if !game_assets.box_background_loaded => {
+                let [r, g, b] = settings.box_interior_color;
+                draw_rectangle(
+                    -box_size.x + box_thickness,
+                    -box_size.y + box_thickness,
                     (box_size.x - box_thickness) * 2.,
                     (box_size.y - box_thickness) * 2.,
-                )),
-                ..Default::default()
-            },
-        );
+                    Color::new(r, g, b, 1.),
+                );
+            }
+            BackgroundMode::Stretch => {
+                draw_texture_ex(
+                    &game_assets.box_background_texture,
+                    -box_size.x + box_thickness,
+                    -box_size.y + box_thickness,
+                    WHITE,
+                    DrawTextureParams {
+                        dest_size: Some(vec2(
+                            (box_size.x - box_thickness) * 2.,
+                            (box_size.y - box_thickness) * 2.,
+                        )),
+                        ..Default::default()
+                    },
+                );
+            }
+            BackgroundMode::Tile => {
+                draw_texture_tiled(
+                    &game_assets.box_background_texture,
+                    -box_size + box_thickness,
+                    (box_size - box_thickness) * 2.,
+                    WHITE,
+                );
+            }
+        }
 
         let max_axis = box_size.max_element();
 
@@ -585,8 +1411,49 @@ async fn main() {
             },
         );
 
+        debug_overlay.render_grid(box_size - box_thickness);
+
+        for (position, radius) in obstacles.iter() {
+            draw_circle(position.x, position.y, *radius, OBSTACLE_COLOR);
+            draw_circle_lines(position.x, position.y, *radius, 3., OBSTACLE_OUTLINE_COLOR);
+        }
+
+        if placing_obstacles {
+            let position = local_mouse_pos * 2. - box_size;
+            draw_circle_lines(
+                position.x,
+                position.y,
+                OBSTACLE_RADIUS,
+                3.,
+                OBSTACLE_OUTLINE_COLOR,
+            );
+        }
+
         // Ball
-        ball.render(&game_assets, &settings, box_size);
+        // With a fixed timestep the ball is drawn between the last two updates, so it moves smoothly at any FPS.
+        let physics_position = ball.position;
+        if settings.fixed_timestep {
+            ball.position = previous_fixed_position
+                .lerp(physics_position, fixed_time_accumulator / FIXED_TIMESTEP);
+        }
+        // Holding End draws the ball upright, so the texture can be checked while it keeps spinning.
+        let physics_rotation = ball.rotation;
+        if is_key_down(settings.keybindings.upright_ball.0) && !ui_renderer.is_typing() {
+            ball.rotation = 0.;
+        }
+        ball.render(&game_assets, &ball_settings, box_size);
+        ball.position = physics_position;
+        ball.rotation = physics_rotation;
+
+        particles.render(&settings);
+
+        if recorder.is_some() {
+            draw_recording_indicator(-box_size + box_thickness + 30.);
+        }
+
+        if ball.gravity_flipped {
+            draw_gravity_flipped_indicator(vec2(0., -box_size.y + box_thickness + 20.));
+        }
 
         if hovering_menu && settings_state.is_open() {
             set_mouse_cursor(CursorIcon::Default);
@@ -596,28 +1463,48 @@ async fn main() {
             set_mouse_cursor(CursorIcon::Pointer);
         }
 
+        // The cursor stays visible in the menu so it's never lost while changing settings.
+        let hide_cursor = settings.cursor_hide_delay > 0.
+            && mouse_idle_time > settings.cursor_hide_delay
+            && !settings_state.is_open();
+
+        if hide_cursor != cursor_hidden {
+            show_mouse(!hide_cursor);
+            cursor_hidden = hide_cursor;
+        }
+
         // Tutorial
-        if time_since_start > MOUSE_TUTORIAL_WAIT {
+        // There's no point in showing how to move the window when it can't be moved.
+        if tutorial_time > MOUSE_TUTORIAL_WAIT && !settings.lock_window {
             render_mouse_tutorial(
                 &game_assets,
-                time_since_start - MOUSE_TUTORIAL_WAIT,
+                tutorial_time - MOUSE_TUTORIAL_WAIT,
                 time_of_understanding_move.and_then(|time| Some(time - MOUSE_TUTORIAL_WAIT)),
                 box_size,
             );
         }
 
         if !settings.understands_menu {
+            render_menu_hint(
+                &game_assets,
+                tutorial_time,
+                box_size,
+                settings.keybindings.open_menu.0,
+            );
+
             if let Some(time_of_understanding_move) = time_of_understanding_move {
-                if time_since_start - time_of_understanding_move > MENU_TUTORIAL_WAIT {
+                if tutorial_time - time_of_understanding_move > MENU_TUTORIAL_WAIT {
                     render_menu_tutorial(
                         &game_assets,
-                        time_since_start - time_of_understanding_move - MENU_TUTORIAL_WAIT,
+                        tutorial_time - time_of_understanding_move - MENU_TUTORIAL_WAIT,
+                        box_size,
                     )
                 }
             }
         }
 
         // Settings
+        ui_renderer.can_undo = settings_before_apply.is_some();
         let save = ui_renderer.render_ui(
             &game_assets,
             &mut editing_settings,
@@ -627,52 +1514,150 @@ async fn main() {
             box_size,
         );
 
+        if ui_renderer.did_copy_settings() {
+            clipboard_set(&editing_settings.serialize_json_pretty());
+        }
+
+        if ui_renderer.did_paste_settings() {
+            match clipboard_get().map(|string| settings_from_json(&string)) {
+                Some(Ok(mut pasted_settings)) => {
+                    // The tutorials shouldn't come back.
+                    pasted_settings.understands_moving = editing_settings.understands_moving;
+                    pasted_settings.understands_menu = editing_settings.understands_menu;
+                    editing_settings = pasted_settings;
+                }
+                Some(Err(err)) => {
+                    error_logs.display_error(format!("Failed to paste settings: {err}"));
+                }
+                None => {
+                    error_logs.display_error(
+                        "Failed to paste settings: The clipboard is empty.".to_string(),
+                    );
+                }
+            }
+        }
+
+        let old_delay_frames = settings.delay_frames;
+
         if save {
-            let change_ball = editing_settings.last_ball != settings.last_ball;
-            let change_sounds = editing_settings.last_sounds != settings.last_sounds;
-            let change_assets = editing_settings.last_asset_pack != settings.last_asset_pack;
-            settings = editing_settings.clone();
+            settings_before_apply = Some(settings.clone());
+            apply_settings(
+                editing_settings.clone(),
+                &mut settings,
+                &mut ball,
+                &mut ball_metadata,
+                &mut game_assets,
+                &mut error_logs,
+            )
+            .await;
+            ball_settings_outdated = true;
+        } else if ui_renderer.did_undo() {
+            if let Some(mut old_settings) = settings_before_apply.take() {
+                // The tutorials shouldn't come back.
+                old_settings.understands_moving = settings.understands_moving;
+                old_settings.understands_menu = settings.understands_menu;
+                editing_settings = old_settings.clone();
+                apply_settings(
+                    old_settings,
+                    &mut settings,
+                    &mut ball,
+                    &mut ball_metadata,
+                    &mut game_assets,
+                    &mut error_logs,
+                )
+                .await;
+                ball_settings_outdated = true;
+            }
+        }
+
+        // The buffered deltas were made for the old amount of delay frames, so replaying them would make the window jump.
+        // This also covers going down to 0, where the buffer otherwise never gets emptied.
+        if settings.delay_frames != old_delay_frames {
+            mouse_deltas.clear();
+        }
+
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.fullscreen.0) {
+            settings.fullscreen = !settings.fullscreen;
+            editing_settings.fullscreen = settings.fullscreen;
             write_settings_file(&settings);
-            for sound in ball.sounds.iter() {
-                set_sound_volume(sound, settings.audio_volume);
+        }
+
+        // Moving the window while it's being dragged or is fullscreen would only get undone.
+        if !ui_renderer.is_typing()
+            && is_key_pressed(settings.keybindings.center_window.0)
+            && !do_drag
+            && !is_fullscreen
+            && !settings.lock_window
+        {
+            let window_position = Vec2::from_i32_tuple(get_window_position());
+            let monitor =
+                find_window_monitor(&monitors, window_position, box_size).or(monitors.first());
+
+            if let Some(monitor) = monitor {
+                let new_pos = monitor.get_centered_position(box_size);
+                set_window_position(new_pos.x as i32, new_pos.y as i32);
+            } else {
+                error_logs.display_error("Centering the window only works on Windows.".to_string());
+            }
+        }
+
+        if settings.fullscreen != is_fullscreen {
+            is_fullscreen = settings.fullscreen;
+            if is_fullscreen {
+                windowed_position = Some(get_window_position());
+                do_drag = false;
+                set_fullscreen(true);
+            } else {
+                set_fullscreen(false);
+                set_window_size(settings.box_width, settings.box_height);
+                if let Some((x, y)) = windowed_position {
+                    set_window_position(x, y);
+                }
             }
-            ball.radius = settings.ball_radius as f32;
-            set_window_size(settings.box_width, settings.box_height);
-            box_size = vec2(settings.box_width as f32, settings.box_height as f32);
+        }
+
+        // In fullscreen the box fills the whole screen.
+        let new_box_size = if is_fullscreen {
+            vec2(screen_width(), screen_height())
+        } else {
+            vec2(settings.box_width as f32, settings.box_height as f32)
+        };
+
+        if new_box_size != box_size {
+            box_size = new_box_size;
             set_camera(&Camera2D {
                 zoom: vec2(1. / box_size.x, 1. / box_size.y),
                 ..Default::default()
             });
-            set_swap_interval(if settings.vsync { 1 } else { 0 });
-            if change_ball {
-                if let Some((_, texture)) = find_texture(&settings.last_ball, &mut error_logs) {
-                    ball.texture = texture
-                }
-            }
+        }
 
-            if change_sounds {
-                if let Some((_, sounds)) = find_sounds(&settings.last_sounds, &mut error_logs).await
-                {
-                    ball.sounds = sounds;
-                }
-            }
+        // Clicks on the background around the rounded box go to the windows behind it.
+        // The window doesn't get the mouse while it's click-through, so this uses the mouse position on the screen.
+        let window_mouse_position =
+            current_mouse_position - Vec2::from_i32_tuple(get_window_position());
+        let clickthrough = settings.clickthrough_background
+            && !is_fullscreen
+            && !do_drag
+            && !is_menu_open
+            && window_mouse_position.cmpge(Vec2::ZERO).all()
+            && window_mouse_position.cmplt(box_size).all()
+            && is_outside_rounded_box(
+                window_mouse_position * 2. - box_size,
+                box_size,
+                (settings.box_corner_radius + settings.box_thickness + settings.box_depth) as f32,
+            );
 
-            if change_assets {
-                let pack_path = if !settings.last_asset_pack.is_empty() {
-                    if let Some((_, pack_path)) =
-                        find_pack(&settings.last_asset_pack, &mut error_logs)
-                    {
-                        Some(pack_path)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
+        if clickthrough != window_clickthrough {
+            set_window_clickthrough(clickthrough);
+            window_clickthrough = clickthrough;
+        }
 
-                game_assets =
-                    GameAssets::new(pack_path, game_assets.missing_texture, &mut error_logs)
-            }
+        if settings.clickthrough_background && !CLICKTHROUGH_SUPPORTED && !warned_about_clickthrough
+        {
+            error_logs.display_error(
+                "Click-through background is not supported on this platform.".to_string(),
+            );
+            warned_about_clickthrough = true;
         }
 
         let ui_interacted = ui_renderer.did_interact();
@@ -710,10 +1695,168 @@ async fn main() {
             }
         }
 
-        error_logs.render_errors(-box_size, box_size.x * 2.);
+        if settings.ui_sounds && !ball.muted {
+            if settings_state.is_open() != is_menu_open {
+                let sounds = if is_menu_open {
+                    &ui_sounds.close
+                } else {
+                    &ui_sounds.open
+                };
+                UiSounds::play(sounds, &settings);
+            } else if ui_renderer.did_click_button() {
+                UiSounds::play(&ui_sounds.click, &settings);
+            }
+        }
+
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.toggle_debug.0) {
+            debug_overlay.enabled = !debug_overlay.enabled;
+        }
+
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.toggle_grid.0) {
+            debug_overlay.cycle_grid();
+        }
+
+        debug_overlay.render(-box_size + box_thickness);
+
+        // Moving the ball outside the physics also moves where the fixed timestep draws it from,
+        // or it would be drawn sliding over from where it was.
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.reset_ball.0) {
+            spawn_ball(&mut ball, &settings, box_size);
+            previous_fixed_position = ball.position;
+            fixed_time_accumulator = 0.;
+        }
+
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.pause.0) {
+            paused = !paused;
+        }
+
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.mute.0) {
+            ball.muted = !ball.muted;
+        }
+
+        // Not saved, so it goes back to normal when the game is restarted.
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.flip_gravity.0) {
+            ball.gravity_flipped = !ball.gravity_flipped;
+        }
+
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.place_obstacles.0) {
+            placing_obstacles = !placing_obstacles;
+        }
+
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.clear_obstacles.0) {
+            obstacles.clear();
+        }
+
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.record.0) {
+            if let Some(recorder) = recorder.take() {
+                recorder.save(&mut error_logs);
+            } else {
+                replay = None;
+                recorder = Some(Recorder::start(&ball));
+            }
+        }
+
+        // Replaying while recording saves the recording first, so it's the one that gets replayed.
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.replay.0) {
+            if let Some(recorder) = recorder.take() {
+                recorder.save(&mut error_logs);
+            }
+            replay = Replay::load(&mut ball, &mut error_logs);
+            previous_fixed_position = ball.position;
+            fixed_time_accumulator = 0.;
+        }
+
+        let ball_offset = if ui_renderer.is_typing() {
+            0
+        } else if is_key_pressed(settings.keybindings.previous_ball.0) {
+            -1
+        } else if is_key_pressed(settings.keybindings.next_ball.0) {
+            1
+        } else {
+            0
+        };
+
+        if ball_offset != 0 {
+            if let Some((ball_name, ball_path)) =
+                ball_cycler.cycle(&settings.last_ball, ball_offset, || {
+                    list_available_balls(&mut error_logs)
+                })
+            {
+                if let Some(texture) = load_ball_texture(&ball_path, &settings, &mut error_logs) {
+                    ball.texture = texture;
+                    ball_metadata = load_ball_metadata(&ball_name, &mut error_logs);
+                    ball_settings_outdated = true;
+                    settings.last_ball = ball_name.clone();
+                    editing_settings.last_ball = ball_name;
+                    write_settings_file(&settings);
+                }
+            }
+        }
+
+        let sounds_offset = if ui_renderer.is_typing() {
+            0
+        } else if is_key_pressed(settings.keybindings.previous_sounds.0) {
+            -1
+        } else if is_key_pressed(settings.keybindings.next_sounds.0) {
+            1
+        } else {
+            0
+        };
+
+        if sounds_offset != 0 {
+            if let Some((sounds_name, sounds_path)) =
+                sounds_cycler.cycle(&settings.last_sounds, sounds_offset, || {
+                    list_available_sounds(&mut error_logs)
+                })
+            {
+                ball.set_sounds(load_sounds(sounds_path, &mut error_logs).await);
+                settings.last_sounds = sounds_name.clone();
+                editing_settings.last_sounds = sounds_name;
+                write_settings_file(&settings);
+            }
+        }
+
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.screenshot.0) {
+            save_screenshot(&mut error_logs);
+        }
+
+        // Makes it easy to find the error log when reporting a bug. The path gets copied in case the folder doesn't open.
+        if !ui_renderer.is_typing() && is_key_pressed(settings.keybindings.show_error_log.0) {
+            let error_log_path = get_absolute_game_path("error_log.txt");
+            clipboard_set(&error_log_path.to_string_lossy());
+            if let Err(err) = show_in_file_manager(&error_log_path) {
+                error_logs.display_error(format!(
+                    "Failed to open the folder of \"{}\": {err}",
+                    error_log_path.to_string_lossy()
+                ));
+            }
+        }
+
+        error_logs.render_errors(
+            -box_size,
+            box_size.x * 2.,
+            settings.max_visible_errors as usize,
+            settings.error_display_time as f64,
+            local_mouse_pos * 2. - box_size,
+        );
+
+        last_mouse_position = current_mouse_position;
+
+        // When nothing has happened for a while, lower the FPS to save power.
+        if had_input || !ball.is_resting() || do_drag || is_menu_open || !mouse_deltas.is_empty() {
+            idle_time = 0.;
+        } else {
+            idle_time += real_delta_time;
+        }
+
+        let max_fps = if settings.idle_throttle && idle_time > IDLE_THROTTLE_WAIT {
+            settings.max_fps.min(IDLE_FPS)
+        } else {
+            settings.max_fps
+        };
 
-        if settings.max_fps < FPS_LIMIT {
-            let min_fps_delta = 1. / settings.max_fps as f64;
+        if max_fps < FPS_LIMIT {
+            let min_fps_delta = 1. / max_fps as f64;
 
             let time_now = get_time();
 
@@ -721,7 +1864,7 @@ async fn main() {
 
             if time_difference < min_fps_delta {
                 let duration = min_fps_delta - time_difference;
-                thread::sleep(Duration::from_secs_f64(duration));
+                wait_precise(duration);
                 prev_render_time = time_now + duration;
             } else {
                 let offset = (time_difference - min_fps_delta) % min_fps_delta;
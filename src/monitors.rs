@@ -0,0 +1,128 @@
+use macroquad::math::Vec2;
+
+/// The area of a monitor that windows can be placed in, in screen coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Monitor {
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+impl Monitor {
+    /// Returns the position a window of the given size needs to be at to be centered on this monitor.
+    pub fn get_centered_position(&self, window_size: Vec2) -> Vec2 {
+        return self.position + (self.size - window_size) / 2.;
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        let end = self.position + self.size;
+        return point.x >= self.position.x
+            && point.y >= self.position.y
+            && point.x < end.x
+            && point.y < end.y;
+    }
+}
+
+/// Returns the monitor the middle of a window is on.
+pub fn find_window_monitor<'a>(
+    monitors: &'a [Monitor],
+    window_position: Vec2,
+    window_size: Vec2,
+) -> Option<&'a Monitor> {
+    let window_center = window_position + window_size / 2.;
+    return monitors
+        .iter()
+        .find(|monitor| monitor.contains(window_center));
+}
+
+/// Lists all the monitors connected. Returns an empty list if the platform isn't supported.
+#[cfg(target_os = "windows")]
+pub fn list_monitors() -> Vec<Monitor> {
+    use macroquad::math::vec2;
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    struct RECT {
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+    }
+
+    #[repr(C)]
+    struct MONITORINFO {
+        cb_size: u32,
+        rc_monitor: RECT,
+        rc_work: RECT,
+        dw_flags: u32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn EnumDisplayMonitors(
+            hdc: *mut c_void,
+            lprc_clip: *const RECT,
+            lpfn_enum: extern "system" fn(*mut c_void, *mut c_void, *mut RECT, isize) -> i32,
+            dw_data: isize,
+        ) -> i32;
+        fn GetMonitorInfoW(h_monitor: *mut c_void, lpmi: *mut MONITORINFO) -> i32;
+    }
+
+    extern "system" fn monitor_enum_proc(
+        h_monitor: *mut c_void,
+        _hdc: *mut c_void,
+        _lprc_monitor: *mut RECT,
+        dw_data: isize,
+    ) -> i32 {
+        let monitors = unsafe { &mut *(dw_data as *mut Vec<Monitor>) };
+
+        let mut info = MONITORINFO {
+            cb_size: std::mem::size_of::<MONITORINFO>() as u32,
+            rc_monitor: RECT {
+                left: 0,
+                top: 0,
+                right: 0,
+                bottom: 0,
+            },
+            rc_work: RECT {
+                left: 0,
+                top: 0,
+                right: 0,
+                bottom: 0,
+            },
+            dw_flags: 0,
+        };
+
+        if unsafe { GetMonitorInfoW(h_monitor, &mut info) } != 0 {
+            let work = info.rc_work;
+            monitors.push(Monitor {
+                position: vec2(work.left as f32, work.top as f32),
+                size: vec2(
+                    (work.right - work.left) as f32,
+                    (work.bottom - work.top) as f32,
+                ),
+            });
+        }
+
+        // Keep enumerating.
+        return 1;
+    }
+
+    let mut monitors: Vec<Monitor> = Vec::new();
+
+    unsafe {
+        EnumDisplayMonitors(
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            monitor_enum_proc,
+            &mut monitors as *mut Vec<Monitor> as isize,
+        );
+    }
+
+    return monitors;
+}
+
+/// Lists all the monitors connected. Returns an empty list if the platform isn't supported.
+#[cfg(not(target_os = "windows"))]
+pub fn list_monitors() -> Vec<Monitor> {
+    return Vec::new();
+}
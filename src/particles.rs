@@ -0,0 +1,97 @@
+use macroquad::{
+    color::Color,
+    math::{vec2, Vec2},
+    rand,
+    shapes::draw_circle,
+};
+
+use crate::{ball::Impact, Settings};
+
+/// Too many particles would slow the game down, so the oldest ones get removed after this.
+const MAX_PARTICLES: usize = 512;
+/// How long a particle lives, in seconds, at an intensity of 1.
+const PARTICLE_LIFETIME: f32 = 0.4;
+const PARTICLE_SIZE: f32 = 5.0;
+/// How much of the impact speed the particles get.
+const PARTICLE_SPEED_MULT: f32 = 0.3;
+/// How far from the bounce direction the particles can fly, in radians.
+const PARTICLE_SPREAD: f32 = 1.2;
+
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    life: f32,
+    max_life: f32,
+}
+
+/// Small dots that fly out from where the ball hits a wall.
+pub struct Particles {
+    particles: Vec<Particle>,
+}
+
+impl Particles {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::new(),
+        }
+    }
+
+    /// Spawns a burst of particles for every impact that was fast enough to make a sound.
+    pub fn spawn_bursts(&mut self, impacts: &[Impact], settings: &Settings) {
+        if settings.particle_count == 0 || settings.particle_intensity <= 0. {
+            return;
+        }
+
+        for impact in impacts {
+            if impact.speed <= settings.min_hit_speed {
+                continue;
+            }
+
+            let base_angle = impact.direction.to_angle();
+            for _ in 0..settings.particle_count {
+                let angle = base_angle + rand::gen_range(-PARTICLE_SPREAD, PARTICLE_SPREAD);
+                let speed = impact.speed
+                    * PARTICLE_SPEED_MULT
+                    * settings.particle_intensity
+                    * rand::gen_range(0.3, 1.0);
+                let max_life = PARTICLE_LIFETIME * settings.particle_intensity.min(2.);
+
+                self.particles.push(Particle {
+                    position: impact.position,
+                    velocity: Vec2::from_angle(angle) * speed,
+                    life: max_life,
+                    max_life,
+                });
+            }
+        }
+
+        if self.particles.len() > MAX_PARTICLES {
+            let extra = self.particles.len() - MAX_PARTICLES;
+            self.particles.drain(0..extra);
+        }
+    }
+
+    pub fn update(&mut self, dt: f32, settings: &Settings) {
+        let gravity = vec2(0., settings.gravity_strength * 1000.);
+        for particle in self.particles.iter_mut() {
+            particle.velocity += gravity * dt;
+            particle.position += particle.velocity * dt;
+            particle.life -= dt;
+        }
+
+        self.particles.retain(|particle| particle.life > 0.);
+    }
+
+    /// Draws the particles, shrinking and fading out as they get older.
+    pub fn render(&self, settings: &Settings) {
+        for particle in self.particles.iter() {
+            let life_left = particle.life / particle.max_life;
+            draw_circle(
+                particle.position.x,
+                particle.position.y,
+                PARTICLE_SIZE * settings.particle_intensity.sqrt() * life_left,
+                Color::new(1., 1., 1., life_left),
+            );
+        }
+    }
+}
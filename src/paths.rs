@@ -0,0 +1,103 @@
+use std::{
+    env, io,
+    path::{self, Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+    thread,
+};
+
+static GAME_DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+
+/// The folder the game files are in.
+///
+/// This is the folder of the executable if it has an `assets` folder next to it, so that launching
+/// the game from another working directory (like a shortcut) still works.
+/// Otherwise it falls back to the working directory, which is what happens when running with cargo.
+pub fn get_game_directory() -> &'static Path {
+    GAME_DIRECTORY.get_or_init(|| {
+        let exe_directory = env::current_exe()
+            .ok()
+            .and_then(|exe_path| exe_path.parent().map(Path::to_path_buf));
+
+        if let Some(exe_directory) = exe_directory {
+            if exe_directory.join("assets").is_dir() {
+                return exe_directory;
+            }
+        }
+
+        return PathBuf::from(".");
+    })
+}
+
+/// Gets the path of a file or folder inside the game directory.
+pub fn get_game_path(relative_path: impl AsRef<Path>) -> PathBuf {
+    return get_game_directory().join(relative_path);
+}
+
+/// Gets the full path of a file or folder inside the game directory, for showing to the user.
+pub fn get_absolute_game_path(relative_path: impl AsRef<Path>) -> PathBuf {
+    let game_path = get_game_path(relative_path);
+    // Not canonicalized, since that gives paths starting with `\\?\` on Windows which look confusing.
+    return path::absolute(&game_path).unwrap_or(game_path);
+}
+
+/// Opens the folder a file is in with the file manager. The file gets selected where that's supported.
+pub fn show_in_file_manager(file_path: &Path) -> io::Result<()> {
+    let folder_path = file_path.parent().unwrap_or(file_path);
+
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("explorer");
+        if file_path.exists() {
+            // Explorer wants the path in the same argument as `/select,`.
+            let mut select = std::ffi::OsString::from("/select,");
+            select.push(file_path);
+            command.arg(select);
+        } else {
+            command.arg(folder_path);
+        }
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        if file_path.exists() {
+            command.arg("-R").arg(file_path);
+        } else {
+            command.arg(folder_path);
+        }
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(folder_path);
+        command
+    };
+
+    let mut child = command.spawn()?;
+    // Waits for the file manager in the background so it doesn't stay around as a zombie process.
+    thread::spawn(move || child.wait());
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_paths_go_in_game_directory() {
+        let path = get_game_path(Path::new("assets").join("ball.png"));
+        assert!(path.starts_with(get_game_directory()));
+        assert!(path.ends_with(Path::new("assets").join("ball.png")));
+    }
+
+    #[test]
+    fn absolute_paths_stay_the_same() {
+        let absolute_path = env::temp_dir().join("ball.png");
+        assert!(absolute_path.is_absolute());
+        assert_eq!(get_game_path(&absolute_path), absolute_path);
+    }
+
+    #[test]
+    fn absolute_game_paths_are_absolute() {
+        let path = get_absolute_game_path("error_log.txt");
+        assert!(path.is_absolute());
+        assert!(path.ends_with("error_log.txt"));
+    }
+}
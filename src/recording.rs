@@ -0,0 +1,124 @@
+use std::fs;
+
+use macroquad::math::{vec2, Vec2};
+use nanoserde::{DeJson, SerJson};
+
+use crate::{ball::Ball, error_log::ErrorLogs, paths::get_game_path};
+
+const RECORDING_FILE: &str = "recording_in_a.json";
+
+/// How the window moved each frame, and how the ball was moving when it started.
+///
+/// The ball only ends up doing the exact same thing if the FPS is the same, so it works best with `Fixed timestep` turned on.
+#[derive(DeJson, SerJson)]
+pub struct Recording {
+    ball_position: [f32; 2],
+    ball_velocity: [f32; 2],
+    ball_rotation: f32,
+    ball_rotation_velocity: f32,
+    /// How much the window was dragged each frame. `None` for frames where it wasn't being dragged.
+    frames: Vec<Option<[f32; 2]>>,
+}
+
+/// Records the window movement until it's stopped.
+pub struct Recorder {
+    recording: Recording,
+    previous_target: Option<Vec2>,
+}
+
+impl Recorder {
+    pub fn start(ball: &Ball) -> Self {
+        Self {
+            recording: Recording {
+                ball_position: ball.position.to_array(),
+                ball_velocity: ball.velocity.to_array(),
+                ball_rotation: ball.rotation,
+                ball_rotation_velocity: ball.rotation_velocity,
+                frames: Vec::new(),
+            },
+            previous_target: None,
+        }
+    }
+
+    /// Adds a frame. `drag_target` is where the window is being dragged to, or `None` if it isn't being dragged.
+    pub fn add_frame(&mut self, drag_target: Option<Vec2>) {
+        let frame = drag_target.map(|drag_target| {
+            let delta = drag_target - self.previous_target.unwrap_or(drag_target);
+            delta.to_array()
+        });
+        self.previous_target = drag_target;
+        self.recording.frames.push(frame);
+    }
+
+    /// Stops recording and saves it, so it can be replayed later.
+    pub fn save(self, error_logs: &mut ErrorLogs) {
+        let path = get_game_path(RECORDING_FILE);
+        if let Err(err) = fs::write(&path, self.recording.serialize_json()) {
+            error_logs.display_error(format!(
+                "Failed to save recording \"{}\": {err}",
+                path.to_string_lossy()
+            ));
+        }
+    }
+}
+
+/// Plays back a saved recording by moving the window like it was moved while recording.
+pub struct Replay {
+    recording: Recording,
+    frame: usize,
+    target: Option<Vec2>,
+}
+
+impl Replay {
+    /// Loads the saved recording and puts the ball back where it was when the recording started.
+    pub fn load(ball: &mut Ball, error_logs: &mut ErrorLogs) -> Option<Self> {
+        let path = get_game_path(RECORDING_FILE);
+        let string = match fs::read_to_string(&path) {
+            Ok(string) => string,
+            Err(err) => {
+                error_logs.display_error(format!(
+                    "Failed to read recording \"{}\": {err}",
+                    path.to_string_lossy()
+                ));
+                return None;
+            }
+        };
+
+        let recording = match Recording::deserialize_json(&string) {
+            Ok(recording) => recording,
+            Err(err) => {
+                error_logs.display_error(format!(
+                    "Failed to parse recording \"{}\": {err}",
+                    path.to_string_lossy()
+                ));
+                return None;
+            }
+        };
+
+        ball.position = Vec2::from_array(recording.ball_position);
+        ball.velocity = Vec2::from_array(recording.ball_velocity);
+        ball.rotation = recording.ball_rotation;
+        ball.rotation_velocity = recording.ball_rotation_velocity;
+
+        Some(Self {
+            recording,
+            frame: 0,
+            target: None,
+        })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.recording.frames.len()
+    }
+
+    /// Returns where the window should be dragged to this frame, or `None` if it wasn't dragged.
+    ///
+    /// `window_position` is where a drag starts from.
+    pub fn next_target(&mut self, window_position: impl FnOnce() -> Vec2) -> Option<Vec2> {
+        let frame = self.recording.frames.get(self.frame).copied().flatten();
+        self.frame += 1;
+
+        self.target = frame.map(|[x, y]| self.target.unwrap_or_else(window_position) + vec2(x, y));
+        self.target
+    }
+}
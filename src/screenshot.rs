@@ -0,0 +1,39 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use macroquad::texture::get_screen_data;
+
+use crate::{error_log::ErrorLogs, paths::get_game_path};
+
+/// Saves what has been rendered so far this frame to a PNG in the game directory.
+pub fn save_screenshot(error_logs: &mut ErrorLogs) {
+    let screen_data = get_screen_data();
+
+    let width = screen_data.width as usize;
+    let height = screen_data.height as usize;
+
+    // The screen data is upside down, so flip it before saving.
+    let mut bytes = Vec::with_capacity(screen_data.bytes.len());
+    for row in screen_data.bytes.chunks_exact(width * 4).rev() {
+        bytes.extend_from_slice(row);
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|err| err.duration())
+        .as_millis();
+
+    let path = get_game_path(format!("screenshot_{timestamp}.png"));
+
+    if let Err(err) = image::save_buffer(
+        &path,
+        &bytes,
+        width as u32,
+        height as u32,
+        image::ColorType::Rgba8,
+    ) {
+        error_logs.display_error(format!(
+            "Failed to save screenshot \"{}\": {err}",
+            path.to_string_lossy()
+        ));
+    }
+}
@@ -1,26 +1,316 @@
 use core::str;
-use std::fs;
+use std::{collections::HashMap, env, fs, ops::Range, str::FromStr};
 
 use macroquad::prelude::*;
 use nanoserde::{DeJson, SerJson};
 
+use crate::{
+    keys::{key_code_from_name, KeyBinding},
+    lang::DEFAULT_LANGUAGE,
+    paths::get_game_path,
+};
+
+/// The version of the settings layout. Bump this when a setting gets renamed or changes meaning,
+/// and add a step to `DeserializeSettings::migrate` for it.
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// The smallest the box width and height sliders go.
+pub const MIN_BOX_SIZE: u32 = 200;
+/// How far the box sliders go, unless the settings file says otherwise.
+pub const DEFAULT_MAX_BOX_WIDTH: u32 = 1200;
+pub const DEFAULT_MAX_BOX_HEIGHT: u32 = 1200;
+pub const DEFAULT_MAX_BOX_THICKNESS: u32 = 100;
+
+/// The anti-aliasing sample counts that can be picked. Other values get lowered to one of these.
+pub const MSAA_SAMPLE_COUNTS: [u32; 4] = [0, 2, 4, 8];
+/// How far the box has to be moved before the tutorial for moving it goes away, unless the settings file says otherwise.
+pub const WINDOW_DISTANCE_BEFORE_UNDERSTAND: f32 = 100.0;
+/// The lowest the max delta time can go, so the game doesn't slow down to a crawl.
+pub const MIN_MAX_DELTA_TIME: f32 = 0.01;
+/// The smallest the ball textures can be scaled down to.
+pub const MIN_TEXTURE_SIZE: u32 = 64;
+
+#[derive(Debug, DeJson, SerJson, Clone, Copy, PartialEq)]
+pub enum DragModel {
+    Linear,
+    Quadratic,
+}
+
+#[derive(Debug, DeJson, SerJson, Clone, Copy, PartialEq)]
+pub enum SmoothingMode {
+    Critical,
+    Spring,
+    Exponential,
+}
+
+/// How the ball gets blended with what's behind it.
+#[derive(Debug, DeJson, SerJson, Clone, Copy, PartialEq)]
+pub enum BallBlendMode {
+    Alpha,
+    /// Brightens what's behind it, for glowing balls.
+    Additive,
+    /// Darkens what's behind it.
+    Multiply,
+}
+
+/// How the typed text gets compared to the names of balls, sounds and asset packs.
+#[derive(Debug, DeJson, SerJson, Clone, Copy, PartialEq)]
+pub enum MatchMode {
+    /// The text ends with the name.
+    Suffix,
+    /// The text is exactly the name.
+    Exact,
+    /// The name is somewhere in the text.
+    Contains,
+}
+
+impl MatchMode {
+    pub fn matches(&self, input: &str, name: &str) -> bool {
+        match self {
+            MatchMode::Suffix => input.ends_with(name),
+            MatchMode::Exact => input == name,
+            MatchMode::Contains => input.contains(name),
+        }
+    }
+}
+
+/// How textures get sampled when they're drawn bigger or smaller than they are.
+#[derive(Debug, DeJson, SerJson, Clone, Copy, PartialEq)]
+pub enum TextureFilter {
+    /// Smooth, for photos and drawn balls.
+    Linear,
+    /// Sharp pixels, for pixel art.
+    Nearest,
+}
+
+impl TextureFilter {
+    pub fn filter_mode(self) -> FilterMode {
+        match self {
+            TextureFilter::Linear => FilterMode::Linear,
+            TextureFilter::Nearest => FilterMode::Nearest,
+        }
+    }
+}
+
+/// How the speed of a hit gets turned into the volume of its sound.
+#[derive(Debug, DeJson, SerJson, Clone, Copy, PartialEq)]
+pub enum VolumeCurve {
+    /// Quiet for soft hits and quickly gets louder, shaped by the hit density.
+    Exponential,
+    /// Goes evenly from silent at the minimum hit speed to full volume at the maximum hit speed.
+    Linear,
+}
+
+/// Which wall the ball sticks to, as if it was magnetic.
+#[derive(Debug, DeJson, SerJson, Clone, Copy, PartialEq)]
+pub enum MagnetWall {
+    Off,
+    Floor,
+    Ceiling,
+    Right,
+    Left,
+}
+
+impl MagnetWall {
+    /// The direction towards the wall, or `None` if the magnet is off.
+    pub fn direction(&self) -> Option<Vec2> {
+        match self {
+            MagnetWall::Off => None,
+            MagnetWall::Floor => Some(vec2(0., 1.)),
+            MagnetWall::Ceiling => Some(vec2(0., -1.)),
+            MagnetWall::Right => Some(vec2(1., 0.)),
+            MagnetWall::Left => Some(vec2(-1., 0.)),
+        }
+    }
+}
+
+#[derive(Debug, DeJson, SerJson, Clone, Copy, PartialEq)]
+pub enum BackgroundMode {
+    Stretch,
+    Tile,
+}
+
+#[derive(Debug, DeJson)]
+#[nserde(serialize_none_as_null)]
+pub struct DeserializeKeyBindings {
+    open_menu: Option<String>,
+    reset_ball: Option<String>,
+    pause: Option<String>,
+    mute: Option<String>,
+    screenshot: Option<String>,
+    fullscreen: Option<String>,
+    slowdown: Option<String>,
+    previous_ball: Option<String>,
+    next_ball: Option<String>,
+    previous_sounds: Option<String>,
+    next_sounds: Option<String>,
+    flip_gravity: Option<String>,
+    record: Option<String>,
+    replay: Option<String>,
+    place_obstacles: Option<String>,
+    clear_obstacles: Option<String>,
+    center_window: Option<String>,
+    show_error_log: Option<String>,
+    toggle_grid: Option<String>,
+    upright_ball: Option<String>,
+    toggle_debug: Option<String>,
+}
+
+impl DeserializeKeyBindings {
+    pub fn contains_none(&self) -> bool {
+        self.open_menu.is_none()
+            || self.reset_ball.is_none()
+            || self.pause.is_none()
+            || self.mute.is_none()
+            || self.screenshot.is_none()
+            || self.fullscreen.is_none()
+            || self.slowdown.is_none()
+            || self.previous_ball.is_none()
+            || self.next_ball.is_none()
+            || self.previous_sounds.is_none()
+            || self.next_sounds.is_none()
+            || self.flip_gravity.is_none()
+            || self.record.is_none()
+            || self.replay.is_none()
+            || self.place_obstacles.is_none()
+            || self.clear_obstacles.is_none()
+            || self.center_window.is_none()
+            || self.show_error_log.is_none()
+            || self.toggle_grid.is_none()
+            || self.upright_ball.is_none()
+            || self.toggle_debug.is_none()
+    }
+
+    /// Keys that are missing or have an unknown name will use the default key.
+    pub fn to_key_bindings(self) -> KeyBindings {
+        let default_key_bindings = KeyBindings::default();
+        let parse_key = |name: Option<String>, default_key: KeyBinding| {
+            name.and_then(|name| key_code_from_name(&name))
+                .map(KeyBinding)
+                .unwrap_or(default_key)
+        };
+        KeyBindings {
+            open_menu: parse_key(self.open_menu, default_key_bindings.open_menu),
+            reset_ball: parse_key(self.reset_ball, default_key_bindings.reset_ball),
+            pause: parse_key(self.pause, default_key_bindings.pause),
+            mute: parse_key(self.mute, default_key_bindings.mute),
+            screenshot: parse_key(self.screenshot, default_key_bindings.screenshot),
+            fullscreen: parse_key(self.fullscreen, default_key_bindings.fullscreen),
+            slowdown: parse_key(self.slowdown, default_key_bindings.slowdown),
+            previous_ball: parse_key(self.previous_ball, default_key_bindings.previous_ball),
+            next_ball: parse_key(self.next_ball, default_key_bindings.next_ball),
+            previous_sounds: parse_key(self.previous_sounds, default_key_bindings.previous_sounds),
+            next_sounds: parse_key(self.next_sounds, default_key_bindings.next_sounds),
+            flip_gravity: parse_key(self.flip_gravity, default_key_bindings.flip_gravity),
+            record: parse_key(self.record, default_key_bindings.record),
+            replay: parse_key(self.replay, default_key_bindings.replay),
+            place_obstacles: parse_key(self.place_obstacles, default_key_bindings.place_obstacles),
+            clear_obstacles: parse_key(self.clear_obstacles, default_key_bindings.clear_obstacles),
+            center_window: parse_key(self.center_window, default_key_bindings.center_window),
+            show_error_log: parse_key(self.show_error_log, default_key_bindings.show_error_log),
+            toggle_grid: parse_key(self.toggle_grid, default_key_bindings.toggle_grid),
+            upright_ball: parse_key(self.upright_ball, default_key_bindings.upright_ball),
+            toggle_debug: parse_key(self.toggle_debug, default_key_bindings.toggle_debug),
+        }
+    }
+}
+
+#[derive(Debug, SerJson, Clone, PartialEq)]
+pub struct KeyBindings {
+    pub open_menu: KeyBinding,
+    pub reset_ball: KeyBinding,
+    pub pause: KeyBinding,
+    pub mute: KeyBinding,
+    pub screenshot: KeyBinding,
+    pub fullscreen: KeyBinding,
+    pub slowdown: KeyBinding,
+    pub previous_ball: KeyBinding,
+    pub next_ball: KeyBinding,
+    pub previous_sounds: KeyBinding,
+    pub next_sounds: KeyBinding,
+    pub flip_gravity: KeyBinding,
+    pub record: KeyBinding,
+    pub replay: KeyBinding,
+    pub place_obstacles: KeyBinding,
+    pub clear_obstacles: KeyBinding,
+    pub center_window: KeyBinding,
+    pub show_error_log: KeyBinding,
+    pub toggle_grid: KeyBinding,
+    pub upright_ball: KeyBinding,
+    pub toggle_debug: KeyBinding,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        // Letters are avoided since typing is used for changing the ball, sounds and asset pack.
+        Self {
+            open_menu: KeyBinding(KeyCode::Escape),
+            reset_ball: KeyBinding(KeyCode::F5),
+            pause: KeyBinding(KeyCode::F6),
+            mute: KeyBinding(KeyCode::F7),
+            screenshot: KeyBinding(KeyCode::F12),
+            fullscreen: KeyBinding(KeyCode::F11),
+            slowdown: KeyBinding(KeyCode::F8),
+            previous_ball: KeyBinding(KeyCode::LeftBracket),
+            next_ball: KeyBinding(KeyCode::RightBracket),
+            previous_sounds: KeyBinding(KeyCode::Comma),
+            next_sounds: KeyBinding(KeyCode::Period),
+            flip_gravity: KeyBinding(KeyCode::F9),
+            record: KeyBinding(KeyCode::F10),
+            replay: KeyBinding(KeyCode::F2),
+            place_obstacles: KeyBinding(KeyCode::Insert),
+            clear_obstacles: KeyBinding(KeyCode::Delete),
+            center_window: KeyBinding(KeyCode::Home),
+            show_error_log: KeyBinding(KeyCode::F1),
+            toggle_grid: KeyBinding(KeyCode::F4),
+            upright_ball: KeyBinding(KeyCode::End),
+            toggle_debug: KeyBinding(KeyCode::F3),
+        }
+    }
+}
+
 #[derive(Debug, DeJson)]
 #[nserde(serialize_none_as_null)]
 pub struct DeserializeSettings {
+    version: Option<u32>,
     audio_volume: Option<f32>,
     hit_density: Option<f32>,
     min_hit_speed: Option<f32>,
+    max_voices: Option<u32>,
+    hit_sound_cooldown: Option<f32>,
+    volume_curve: Option<VolumeCurve>,
+    max_hit_speed: Option<f32>,
+    corner_emphasis: Option<f32>,
+    ui_sounds: Option<bool>,
+    roll_sound: Option<bool>,
     gravity_strength: Option<f32>,
     air_friction: Option<f32>,
+    drag_model: Option<DragModel>,
     max_velocity: Option<f32>,
     ball_bounciness: Option<f32>,
+    energy_decay: Option<f32>,
     ball_radius: Option<f32>,
     ball_weight: Option<f32>,
     ball_friction: Option<f32>,
+    floor_friction_mult: Option<f32>,
+    wall_friction_mult: Option<f32>,
+    bounce_falloff: Option<f32>,
+    velocity_filter: Option<f32>,
+    spin_friction: Option<f32>,
+    gravity_gradient: Option<f32>,
+    min_bounce_speed: Option<f32>,
+    soft_collision: Option<bool>,
+    soft_collision_stiffness: Option<f32>,
+    throw_strength: Option<f32>,
+    weight_throw_influence: Option<f32>,
     box_width: Option<f32>,
     box_height: Option<f32>,
     box_thickness: Option<f32>,
     box_depth: Option<f32>,
+    box_corner_radius: Option<f32>,
+    max_box_width: Option<f32>,
+    max_box_height: Option<f32>,
+    max_box_thickness: Option<f32>,
     ambient_occlusion_focus: Option<f32>,
     ambient_occlusion_strength: Option<f32>,
     specular_focus: Option<f32>,
@@ -29,15 +319,66 @@ pub struct DeserializeSettings {
     shadow_size: Option<f32>,
     shadow_distance_strength: Option<f32>,
     shadow_strength: Option<f32>,
+    shadow_color: Option<[f32; 3]>,
+    box_interior_color: Option<[f32; 3]>,
+    background_mode: Option<BackgroundMode>,
+    glass_overlay: Option<bool>,
+    glass_overlay_intensity: Option<f32>,
+    ball_blend_mode: Option<BallBlendMode>,
+    msaa_samples: Option<u32>,
+    particle_count: Option<u32>,
+    particle_intensity: Option<f32>,
+    light_angle: Option<f32>,
+    light_height: Option<f32>,
+    max_texture_size: Option<u32>,
+    texture_filter: Option<TextureFilter>,
+    smooth_edge: Option<bool>,
     delay_frames: Option<u32>,
     max_fps: Option<u32>,
     speed_mul: Option<f32>,
     vsync: Option<bool>,
+    fixed_timestep: Option<bool>,
+    max_delta_time: Option<f32>,
+    box_weight_x: Option<f32>,
+    box_weight_y: Option<f32>,
+    // Only read when migrating.
     box_weight: Option<f32>,
     hide_smoothing: Option<bool>,
     quick_turn: Option<bool>,
+    max_window_speed: Option<f32>,
+    recoil_strength: Option<f32>,
+    smoothing_mode: Option<SmoothingMode>,
     last_ball: Option<String>,
     click_to_drag: Option<bool>,
+    spawn_at_cursor: Option<bool>,
+    idle_throttle: Option<bool>,
+    free_roam: Option<bool>,
+    flip_with_direction: Option<bool>,
+    fullscreen: Option<bool>,
+    slowdown_factor: Option<f32>,
+    grab_ball: Option<bool>,
+    cursor_gravity: Option<bool>,
+    cursor_gravity_strength: Option<f32>,
+    match_mode: Option<MatchMode>,
+    idle_bob: Option<f32>,
+    clickthrough_background: Option<bool>,
+    max_visible_errors: Option<u32>,
+    error_display_time: Option<f32>,
+    open_menu_on_start: Option<bool>,
+    attract_mode: Option<bool>,
+    attract_delay: Option<f32>,
+    language: Option<String>,
+    magnet_wall: Option<MagnetWall>,
+    lock_window: Option<bool>,
+    cursor_hide_delay: Option<f32>,
+    tutorial_move_distance: Option<f32>,
+    keybindings: Option<DeserializeKeyBindings>,
+    ranges: Option<HashMap<String, f32>>,
+    ball_start_rotation: Option<f32>,
+    // Missing and null both mean no monitor was chosen, so it's not checked in contains_none.
+    start_monitor: Option<u32>,
+    // Missing and null both mean the seed comes from the clock.
+    random_seed: Option<u64>,
     last_sounds: Option<String>,
     last_asset_pack: Option<String>,
     understands_moving: Option<bool>,
@@ -45,6 +386,30 @@ pub struct DeserializeSettings {
 }
 
 impl DeserializeSettings {
+    /// Upgrades settings saved with an older layout to the current one.
+    ///
+    /// Returns true if anything had to be upgraded, so the file can be written again.
+    pub fn migrate(&mut self) -> bool {
+        let version = self.version.unwrap_or(0);
+
+        if version >= SETTINGS_VERSION {
+            return false;
+        }
+
+        // Files from before there was a version have the same layout as version 1, so they only get the version added.
+
+        if version < 2 {
+            // The box weight got split into one for each axis.
+            if let Some(box_weight) = self.box_weight.take() {
+                self.box_weight_x = self.box_weight_x.or(Some(box_weight));
+                self.box_weight_y = self.box_weight_y.or(Some(box_weight));
+            }
+        }
+
+        self.version = Some(SETTINGS_VERSION);
+        return true;
+    }
+
     pub fn contains_none(&self) -> bool {
         self.audio_volume.is_none()
             || self.hit_density.is_none()
@@ -59,6 +424,8 @@ impl DeserializeSettings {
             || self.box_width.is_none()
             || self.box_height.is_none()
             || self.box_thickness.is_none()
+            || self.box_weight_x.is_none()
+            || self.box_weight_y.is_none()
             || self.box_depth.is_none()
             || self.ambient_occlusion_focus.is_none()
             || self.ambient_occlusion_strength.is_none()
@@ -74,28 +441,118 @@ impl DeserializeSettings {
             || self.hide_smoothing.is_none()
             || self.quick_turn.is_none()
             || self.click_to_drag.is_none()
+            || self.spawn_at_cursor.is_none()
             || self.last_ball.is_none()
             || self.last_sounds.is_none()
             || self.last_asset_pack.is_none()
             || self.understands_moving.is_none()
             || self.understands_menu.is_none()
+            || self.drag_model.is_none()
+            || self.throw_strength.is_none()
+            || self.idle_throttle.is_none()
+            || self.smoothing_mode.is_none()
+            || self.energy_decay.is_none()
+            || self.background_mode.is_none()
+            || self.free_roam.is_none()
+            || self.shadow_color.is_none()
+            || self.ball_start_rotation.is_none()
+            || self.max_voices.is_none()
+            || self.flip_with_direction.is_none()
+            || self
+                .keybindings
+                .as_ref()
+                .map_or(true, |keybindings| keybindings.contains_none())
+            || self.ranges.is_none()
+            || self.fullscreen.is_none()
+            || self.slowdown_factor.is_none()
+            || self.grab_ball.is_none()
+            || self.box_corner_radius.is_none()
+            || self.weight_throw_influence.is_none()
+            || self.glass_overlay.is_none()
+            || self.glass_overlay_intensity.is_none()
+            || self.cursor_gravity.is_none()
+            || self.cursor_gravity_strength.is_none()
+            || self.match_mode.is_none()
+            || self.ball_blend_mode.is_none()
+            || self.idle_bob.is_none()
+            || self.floor_friction_mult.is_none()
+            || self.wall_friction_mult.is_none()
+            || self.fixed_timestep.is_none()
+            || self.clickthrough_background.is_none()
+            || self.max_visible_errors.is_none()
+            || self.attract_mode.is_none()
+            || self.attract_delay.is_none()
+            || self.max_box_width.is_none()
+            || self.max_box_height.is_none()
+            || self.max_box_thickness.is_none()
+            || self.bounce_falloff.is_none()
+            || self.language.is_none()
+            || self.msaa_samples.is_none()
+            || self.magnet_wall.is_none()
+            || self.hit_sound_cooldown.is_none()
+            || self.particle_count.is_none()
+            || self.particle_intensity.is_none()
+            || self.light_angle.is_none()
+            || self.light_height.is_none()
+            || self.lock_window.is_none()
+            || self.volume_curve.is_none()
+            || self.max_hit_speed.is_none()
+            || self.cursor_hide_delay.is_none()
+            || self.max_texture_size.is_none()
+            || self.texture_filter.is_none()
+            || self.corner_emphasis.is_none()
+            || self.velocity_filter.is_none()
+            || self.max_delta_time.is_none()
+            || self.tutorial_move_distance.is_none()
+            || self.error_display_time.is_none()
+            || self.spin_friction.is_none()
+            || self.box_interior_color.is_none()
+            || self.max_window_speed.is_none()
+            || self.ui_sounds.is_none()
+            || self.gravity_gradient.is_none()
+            || self.min_bounce_speed.is_none()
+            || self.soft_collision.is_none()
+            || self.soft_collision_stiffness.is_none()
+            || self.roll_sound.is_none()
+            || self.recoil_strength.is_none()
+            || self.smooth_edge.is_none()
+            || self.open_menu_on_start.is_none()
     }
 
     pub fn to_settings(self) -> (Settings, bool) {
         let default_settings = Settings::default();
         let has_none = self.contains_none();
-        let settings = Settings {
+        let mut settings = Settings {
+            version: SETTINGS_VERSION,
             audio_volume: self.audio_volume.unwrap_or(default_settings.audio_volume),
             hit_density: self.hit_density.unwrap_or(default_settings.hit_density),
             min_hit_speed: self.min_hit_speed.unwrap_or(default_settings.min_hit_speed),
+            max_voices: self
+                .max_voices
+                .unwrap_or(default_settings.max_voices)
+                .max(1),
+            hit_sound_cooldown: self
+                .hit_sound_cooldown
+                .map(|hit_sound_cooldown| hit_sound_cooldown.max(0.))
+                .unwrap_or(default_settings.hit_sound_cooldown),
+            volume_curve: self.volume_curve.unwrap_or(default_settings.volume_curve),
+            max_hit_speed: self.max_hit_speed.unwrap_or(default_settings.max_hit_speed),
+            corner_emphasis: self
+                .corner_emphasis
+                .map(|corner_emphasis| corner_emphasis.max(0.))
+                .unwrap_or(default_settings.corner_emphasis),
+            ui_sounds: self.ui_sounds.unwrap_or(default_settings.ui_sounds),
+            roll_sound: self.roll_sound.unwrap_or(default_settings.roll_sound),
             gravity_strength: self
                 .gravity_strength
                 .unwrap_or(default_settings.gravity_strength),
             air_friction: self.air_friction.unwrap_or(default_settings.air_friction),
+            drag_model: self.drag_model.unwrap_or(default_settings.drag_model),
             max_velocity: self.max_velocity.unwrap_or(default_settings.max_velocity),
             ball_bounciness: self
                 .ball_bounciness
                 .unwrap_or(default_settings.ball_bounciness),
+            energy_decay: self.energy_decay.unwrap_or(default_settings.energy_decay),
             ball_radius: self
                 .ball_radius
                 .and_then(|ball_radius| {
@@ -108,6 +565,44 @@ impl DeserializeSettings {
                 .unwrap_or(default_settings.ball_radius),
             ball_weight: self.ball_weight.unwrap_or(default_settings.ball_weight),
             ball_friction: self.ball_friction.unwrap_or(default_settings.ball_friction),
+            floor_friction_mult: self
+                .floor_friction_mult
+                .unwrap_or(default_settings.floor_friction_mult),
+            wall_friction_mult: self
+                .wall_friction_mult
+                .unwrap_or(default_settings.wall_friction_mult),
+            bounce_falloff: self
+                .bounce_falloff
+                .map(|bounce_falloff| bounce_falloff.max(0.))
+                .unwrap_or(default_settings.bounce_falloff),
+            velocity_filter: self
+                .velocity_filter
+                .map(|velocity_filter| velocity_filter.max(0.))
+                .unwrap_or(default_settings.velocity_filter),
+            spin_friction: self
+                .spin_friction
+                .map(|spin_friction| spin_friction.max(0.))
+                .unwrap_or(default_settings.spin_friction),
+            gravity_gradient: self
+                .gravity_gradient
+                .unwrap_or(default_settings.gravity_gradient),
+            min_bounce_speed: self
+                .min_bounce_speed
+                .map(|min_bounce_speed| min_bounce_speed.max(0.))
+                .unwrap_or(default_settings.min_bounce_speed),
+            soft_collision: self
+                .soft_collision
+                .unwrap_or(default_settings.soft_collision),
+            soft_collision_stiffness: self
+                .soft_collision_stiffness
+                .map(|stiffness| stiffness.max(1.))
+                .unwrap_or(default_settings.soft_collision_stiffness),
+            throw_strength: self
+                .throw_strength
+                .unwrap_or(default_settings.throw_strength),
+            weight_throw_influence: self
+                .weight_throw_influence
+                .unwrap_or(default_settings.weight_throw_influence),
             box_width: self
                 .box_width
                 .and_then(|box_width| {
@@ -148,6 +643,22 @@ impl DeserializeSettings {
                     }
                 })
                 .unwrap_or(default_settings.box_depth),
+            box_corner_radius: self
+                .box_corner_radius
+                .map(|box_corner_radius| box_corner_radius.max(0.) as u32)
+                .unwrap_or(default_settings.box_corner_radius),
+            max_box_width: self
+                .max_box_width
+                .map(|max_box_width| max_box_width.max(MIN_BOX_SIZE as f32) as u32)
+                .unwrap_or(default_settings.max_box_width),
+            max_box_height: self
+                .max_box_height
+                .map(|max_box_height| max_box_height.max(MIN_BOX_SIZE as f32) as u32)
+                .unwrap_or(default_settings.max_box_height),
+            max_box_thickness: self
+                .max_box_thickness
+                .map(|max_box_thickness| max_box_thickness.max(1.) as u32)
+                .unwrap_or(default_settings.max_box_thickness),
             ambient_occlusion_focus: self
                 .ambient_occlusion_focus
                 .unwrap_or(default_settings.ambient_occlusion_focus),
@@ -164,6 +675,51 @@ impl DeserializeSettings {
             shadow_strength: self
                 .shadow_strength
                 .unwrap_or(default_settings.shadow_strength),
+            shadow_color: self.shadow_color.unwrap_or(default_settings.shadow_color),
+            box_interior_color: self
+                .box_interior_color
+                .map(|color| color.map(|channel| channel.clamp(0., 1.)))
+                .unwrap_or(default_settings.box_interior_color),
+            background_mode: self
+                .background_mode
+                .unwrap_or(default_settings.background_mode),
+            glass_overlay: self.glass_overlay.unwrap_or(default_settings.glass_overlay),
+            glass_overlay_intensity: self
+                .glass_overlay_intensity
+                .unwrap_or(default_settings.glass_overlay_intensity),
+            ball_blend_mode: self
+                .ball_blend_mode
+                .unwrap_or(default_settings.ball_blend_mode),
+            msaa_samples: self
+                .msaa_samples
+                .map(|msaa_samples| {
+                    MSAA_SAMPLE_COUNTS
+                        .into_iter()
+                        .filter(|count| *count <= msaa_samples)
+                        .max()
+                        .unwrap_or(0)
+                })
+                .unwrap_or(default_settings.msaa_samples),
+            particle_count: self
+                .particle_count
+                .unwrap_or(default_settings.particle_count),
+            particle_intensity: self
+                .particle_intensity
+                .map(|particle_intensity| particle_intensity.max(0.))
+                .unwrap_or(default_settings.particle_intensity),
+            light_angle: self.light_angle.unwrap_or(default_settings.light_angle),
+            light_height: self
+                .light_height
+                .map(|light_height| light_height.max(0.))
+                .unwrap_or(default_settings.light_height),
+            max_texture_size: self
+                .max_texture_size
+                .unwrap_or(default_settings.max_texture_size)
+                .max(MIN_TEXTURE_SIZE),
+            texture_filter: self
+                .texture_filter
+                .unwrap_or(default_settings.texture_filter),
+            smooth_edge: self.smooth_edge.unwrap_or(default_settings.smooth_edge),
             shadow_size: self.shadow_size.unwrap_or(default_settings.shadow_size),
             shadow_distance_strength: self
                 .shadow_distance_strength
@@ -172,12 +728,87 @@ impl DeserializeSettings {
             max_fps: self.max_fps.unwrap_or(default_settings.max_fps).max(1),
             speed_mul: self.speed_mul.unwrap_or(default_settings.speed_mul),
             vsync: self.vsync.unwrap_or(default_settings.vsync),
-            box_weight: self.box_weight.unwrap_or(default_settings.box_weight),
+            fixed_timestep: self
+                .fixed_timestep
+                .unwrap_or(default_settings.fixed_timestep),
+            max_delta_time: self
+                .max_delta_time
+                .map(|max_delta_time| max_delta_time.max(MIN_MAX_DELTA_TIME))
+                .unwrap_or(default_settings.max_delta_time),
+            box_weight_x: self.box_weight_x.unwrap_or(default_settings.box_weight_x),
+            box_weight_y: self.box_weight_y.unwrap_or(default_settings.box_weight_y),
             hide_smoothing: self
                 .hide_smoothing
                 .unwrap_or(default_settings.hide_smoothing),
             click_to_drag: self.click_to_drag.unwrap_or(default_settings.click_to_drag),
+            spawn_at_cursor: self
+                .spawn_at_cursor
+                .unwrap_or(default_settings.spawn_at_cursor),
+            idle_throttle: self.idle_throttle.unwrap_or(default_settings.idle_throttle),
+            free_roam: self.free_roam.unwrap_or(default_settings.free_roam),
+            flip_with_direction: self
+                .flip_with_direction
+                .unwrap_or(default_settings.flip_with_direction),
+            fullscreen: self.fullscreen.unwrap_or(default_settings.fullscreen),
+            slowdown_factor: self
+                .slowdown_factor
+                .unwrap_or(default_settings.slowdown_factor),
+            grab_ball: self.grab_ball.unwrap_or(default_settings.grab_ball),
+            cursor_gravity: self
+                .cursor_gravity
+                .unwrap_or(default_settings.cursor_gravity),
+            cursor_gravity_strength: self
+                .cursor_gravity_strength
+                .unwrap_or(default_settings.cursor_gravity_strength),
+            match_mode: self.match_mode.unwrap_or(default_settings.match_mode),
+            idle_bob: self.idle_bob.unwrap_or(default_settings.idle_bob),
+            clickthrough_background: self
+                .clickthrough_background
+                .unwrap_or(default_settings.clickthrough_background),
+            max_visible_errors: self
+                .max_visible_errors
+                .unwrap_or(default_settings.max_visible_errors),
+            error_display_time: self
+                .error_display_time
+                .map(|error_display_time| error_display_time.max(0.))
+                .unwrap_or(default_settings.error_display_time),
+            open_menu_on_start: self
+                .open_menu_on_start
+                .unwrap_or(default_settings.open_menu_on_start),
+            attract_mode: self.attract_mode.unwrap_or(default_settings.attract_mode),
+            attract_delay: self.attract_delay.unwrap_or(default_settings.attract_delay),
+            language: self.language.unwrap_or(default_settings.language),
+            magnet_wall: self.magnet_wall.unwrap_or(default_settings.magnet_wall),
+            lock_window: self.lock_window.unwrap_or(default_settings.lock_window),
+            cursor_hide_delay: self
+                .cursor_hide_delay
+                .unwrap_or(default_settings.cursor_hide_delay),
+            tutorial_move_distance: self
+                .tutorial_move_distance
+                .map(|tutorial_move_distance| tutorial_move_distance.max(0.))
+                .unwrap_or(default_settings.tutorial_move_distance),
+            keybindings: self
+                .keybindings
+                .map(|keybindings| keybindings.to_key_bindings())
+                .unwrap_or(default_settings.keybindings),
+            ranges: self.ranges.unwrap_or(default_settings.ranges),
+            ball_start_rotation: self
+                .ball_start_rotation
+                .unwrap_or(default_settings.ball_start_rotation),
+            start_monitor: self.start_monitor,
+            random_seed: self.random_seed,
             quick_turn: self.quick_turn.unwrap_or(default_settings.quick_turn),
+            max_window_speed: self
+                .max_window_speed
+                .map(|max_window_speed| max_window_speed.max(0.))
+                .unwrap_or(default_settings.max_window_speed),
+            recoil_strength: self
+                .recoil_strength
+                .map(|recoil_strength| recoil_strength.max(0.))
+                .unwrap_or(default_settings.recoil_strength),
+            smoothing_mode: self
+                .smoothing_mode
+                .unwrap_or(default_settings.smoothing_mode),
             last_ball: self.last_ball.unwrap_or(default_settings.last_ball),
             last_sounds: self.last_sounds.unwrap_or(default_settings.last_sounds),
             last_asset_pack: self
@@ -190,6 +821,7 @@ impl DeserializeSettings {
                 .understands_menu
                 .unwrap_or(default_settings.understands_menu),
         };
+        settings.validate_box_size();
         (settings, has_none)
     }
 }
@@ -197,9 +829,18 @@ impl DeserializeSettings {
 #[derive(Debug, SerJson, Clone, PartialEq)]
 #[nserde(serialize_none_as_null)]
 pub struct Settings {
+    pub version: u32,
+
     pub audio_volume: f32,
     pub hit_density: f32,
     pub min_hit_speed: f32,
+    pub max_voices: u32,
+    pub hit_sound_cooldown: f32,
+    pub volume_curve: VolumeCurve,
+    pub max_hit_speed: f32,
+    pub corner_emphasis: f32,
+    pub ui_sounds: bool,
+    pub roll_sound: bool,
 
     pub ambient_occlusion_focus: f32,
     pub ambient_occlusion_strength: f32,
@@ -209,30 +850,97 @@ pub struct Settings {
     pub shadow_size: f32,
     pub shadow_distance_strength: f32,
     pub shadow_strength: f32,
+    pub shadow_color: [f32; 3],
+    pub box_interior_color: [f32; 3],
+    pub background_mode: BackgroundMode,
+    pub glass_overlay: bool,
+    pub glass_overlay_intensity: f32,
+    pub ball_blend_mode: BallBlendMode,
+    pub msaa_samples: u32,
+    pub particle_count: u32,
+    pub particle_intensity: f32,
+    pub light_angle: f32,
+    pub light_height: f32,
+    pub max_texture_size: u32,
+    pub texture_filter: TextureFilter,
+    pub smooth_edge: bool,
 
-    pub box_weight: f32,
+    pub box_weight_x: f32,
+    pub box_weight_y: f32,
     pub hide_smoothing: bool,
     pub quick_turn: bool,
+    pub max_window_speed: f32,
+    pub recoil_strength: f32,
+    pub smoothing_mode: SmoothingMode,
 
     pub box_width: u32,
     pub box_height: u32,
     pub box_thickness: u32,
     pub box_depth: u32,
+    pub box_corner_radius: u32,
+    pub max_box_width: u32,
+    pub max_box_height: u32,
+    pub max_box_thickness: u32,
 
     pub gravity_strength: f32,
     pub air_friction: f32,
+    pub drag_model: DragModel,
     pub max_velocity: f32,
     pub ball_bounciness: f32,
+    pub energy_decay: f32,
     pub ball_weight: f32,
     pub ball_friction: f32,
+    pub floor_friction_mult: f32,
+    pub wall_friction_mult: f32,
+    pub bounce_falloff: f32,
+    pub velocity_filter: f32,
+    pub spin_friction: f32,
+    pub gravity_gradient: f32,
+    pub min_bounce_speed: f32,
+    pub soft_collision: bool,
+    pub soft_collision_stiffness: f32,
+    pub throw_strength: f32,
+    pub weight_throw_influence: f32,
 
     pub delay_frames: u32,
     pub max_fps: u32,
     pub vsync: bool,
+    pub fixed_timestep: bool,
+    pub max_delta_time: f32,
 
     pub ball_radius: u32,
     pub speed_mul: f32,
     pub click_to_drag: bool,
+    pub spawn_at_cursor: bool,
+    pub idle_throttle: bool,
+    pub free_roam: bool,
+    pub flip_with_direction: bool,
+    pub fullscreen: bool,
+    pub slowdown_factor: f32,
+    pub grab_ball: bool,
+    pub cursor_gravity: bool,
+    pub cursor_gravity_strength: f32,
+    pub match_mode: MatchMode,
+    pub idle_bob: f32,
+    pub clickthrough_background: bool,
+    pub max_visible_errors: u32,
+    pub error_display_time: f32,
+    pub open_menu_on_start: bool,
+    pub attract_mode: bool,
+    pub attract_delay: f32,
+    pub language: String,
+    pub magnet_wall: MagnetWall,
+    pub lock_window: bool,
+    pub cursor_hide_delay: f32,
+    /// How far the box has to be moved before the tutorial for moving it goes away.
+    pub tutorial_move_distance: f32,
+    pub keybindings: KeyBindings,
+    /// Overrides for how far the sliders go, like `gravity_strength_min` and `gravity_strength_max`.
+    /// Only changed in the settings file.
+    pub ranges: HashMap<String, f32>,
+    pub ball_start_rotation: f32,
+    pub start_monitor: Option<u32>,
+    pub random_seed: Option<u64>,
 
     pub last_ball: String,
     pub last_sounds: String,
@@ -243,10 +951,54 @@ pub struct Settings {
 }
 
 impl Settings {
+    /// The smallest the box can be while still having room for the ball inside of the walls.
+    pub fn min_box_size(&self) -> u32 {
+        self.box_thickness + self.box_depth + self.ball_radius
+    }
+
+    /// Keeps the box size within its limits, making it bigger if the ball wouldn't fit.
+    pub fn validate_box_size(&mut self) {
+        self.box_thickness = self.box_thickness.min(self.max_box_thickness);
+        let min_box_size = self.min_box_size();
+        self.max_box_width = self.max_box_width.max(min_box_size);
+        self.max_box_height = self.max_box_height.max(min_box_size);
+        self.box_width = self.box_width.min(self.max_box_width).max(min_box_size);
+        self.box_height = self.box_height.min(self.max_box_height).max(min_box_size);
+    }
+
+    /// The range of the slider for a setting, with the overrides from `ranges` applied.
+    ///
+    /// Overrides that would make the range empty are ignored.
+    pub fn slider_range(&self, name: &str, default_range: Range<f32>) -> Range<f32> {
+        let min = self
+            .ranges
+            .get(&format!("{name}_min"))
+            .copied()
+            .unwrap_or(default_range.start);
+        let max = self
+            .ranges
+            .get(&format!("{name}_max"))
+            .copied()
+            .unwrap_or(default_range.end);
+
+        if min < max {
+            return min..max;
+        }
+
+        return default_range;
+    }
+
     pub fn audio_changed(&self, compare: &Settings) -> bool {
         self.audio_volume != compare.audio_volume
             || self.hit_density != compare.hit_density
             || self.min_hit_speed != compare.min_hit_speed
+            || self.max_voices != compare.max_voices
+            || self.hit_sound_cooldown != compare.hit_sound_cooldown
+            || self.volume_curve != compare.volume_curve
+            || self.max_hit_speed != compare.max_hit_speed
+            || self.corner_emphasis != compare.corner_emphasis
+            || self.ui_sounds != compare.ui_sounds
+            || self.roll_sound != compare.roll_sound
     }
 
     pub fn visual_changed(&self, compare: &Settings) -> bool {
@@ -258,16 +1010,35 @@ impl Settings {
             || self.shadow_size != compare.shadow_size
             || self.shadow_distance_strength != compare.shadow_distance_strength
             || self.shadow_strength != compare.shadow_strength
+            || self.background_mode != compare.background_mode
+            || self.shadow_color != compare.shadow_color
+            || self.glass_overlay != compare.glass_overlay
+            || self.glass_overlay_intensity != compare.glass_overlay_intensity
+            || self.ball_blend_mode != compare.ball_blend_mode
+            || self.msaa_samples != compare.msaa_samples
+            || self.particle_count != compare.particle_count
+            || self.particle_intensity != compare.particle_intensity
+            || self.light_angle != compare.light_angle
+            || self.light_height != compare.light_height
+            || self.max_texture_size != compare.max_texture_size
+            || self.texture_filter != compare.texture_filter
+            || self.box_interior_color != compare.box_interior_color
+            || self.smooth_edge != compare.smooth_edge
     }
 
     pub fn box_changed(&self, compare: &Settings) -> bool {
-        self.box_weight != compare.box_weight
+        self.box_weight_x != compare.box_weight_x
+            || self.box_weight_y != compare.box_weight_y
             || self.hide_smoothing != compare.hide_smoothing
             || self.quick_turn != compare.quick_turn
             || self.box_width != compare.box_width
             || self.box_height != compare.box_height
             || self.box_thickness != compare.box_thickness
             || self.box_depth != compare.box_depth
+            || self.smoothing_mode != compare.smoothing_mode
+            || self.box_corner_radius != compare.box_corner_radius
+            || self.max_window_speed != compare.max_window_speed
+            || self.recoil_strength != compare.recoil_strength
     }
 
     pub fn physics_changed(&self, compare: &Settings) -> bool {
@@ -277,43 +1048,226 @@ impl Settings {
             || self.ball_bounciness != compare.ball_bounciness
             || self.ball_weight != compare.ball_weight
             || self.ball_friction != compare.ball_friction
+            || self.drag_model != compare.drag_model
+            || self.throw_strength != compare.throw_strength
+            || self.energy_decay != compare.energy_decay
+            || self.weight_throw_influence != compare.weight_throw_influence
+            || self.floor_friction_mult != compare.floor_friction_mult
+            || self.wall_friction_mult != compare.wall_friction_mult
+            || self.bounce_falloff != compare.bounce_falloff
+            || self.velocity_filter != compare.velocity_filter
+            || self.spin_friction != compare.spin_friction
+            || self.gravity_gradient != compare.gravity_gradient
+            || self.min_bounce_speed != compare.min_bounce_speed
+            || self.soft_collision != compare.soft_collision
+            || self.soft_collision_stiffness != compare.soft_collision_stiffness
     }
 
     pub fn fps_delay_changed(&self, compare: &Settings) -> bool {
         self.delay_frames != compare.delay_frames
             || self.max_fps != compare.max_fps
             || self.vsync != compare.vsync
+            || self.fixed_timestep != compare.fixed_timestep
+            || self.max_delta_time != compare.max_delta_time
     }
 
     pub fn misc_changed(&self, compare: &Settings) -> bool {
         self.ball_radius != compare.ball_radius
             || self.speed_mul != compare.speed_mul
             || self.click_to_drag != compare.click_to_drag
+            || self.spawn_at_cursor != compare.spawn_at_cursor
             || self.last_ball != compare.last_ball
             || self.last_sounds != compare.last_sounds
             || self.last_asset_pack != compare.last_asset_pack
+            || self.idle_throttle != compare.idle_throttle
+            || self.start_monitor != compare.start_monitor
+            || self.random_seed != compare.random_seed
+            || self.free_roam != compare.free_roam
+            || self.ball_start_rotation != compare.ball_start_rotation
+            || self.flip_with_direction != compare.flip_with_direction
+            || self.keybindings != compare.keybindings
+            || self.fullscreen != compare.fullscreen
+            || self.slowdown_factor != compare.slowdown_factor
+            || self.grab_ball != compare.grab_ball
+            || self.cursor_gravity != compare.cursor_gravity
+            || self.cursor_gravity_strength != compare.cursor_gravity_strength
+            || self.match_mode != compare.match_mode
+            || self.idle_bob != compare.idle_bob
+            || self.clickthrough_background != compare.clickthrough_background
+            || self.max_visible_errors != compare.max_visible_errors
+            || self.attract_mode != compare.attract_mode
+            || self.attract_delay != compare.attract_delay
+            || self.language != compare.language
+            || self.magnet_wall != compare.magnet_wall
+            || self.lock_window != compare.lock_window
+            || self.cursor_hide_delay != compare.cursor_hide_delay
+            || self.error_display_time != compare.error_display_time
+            || self.open_menu_on_start != compare.open_menu_on_start
+    }
+
+    pub fn reset_audio(&mut self, default_settings: &Settings) {
+        self.audio_volume = default_settings.audio_volume;
+        self.hit_density = default_settings.hit_density;
+        self.min_hit_speed = default_settings.min_hit_speed;
+        self.max_voices = default_settings.max_voices;
+        self.hit_sound_cooldown = default_settings.hit_sound_cooldown;
+        self.volume_curve = default_settings.volume_curve;
+        self.max_hit_speed = default_settings.max_hit_speed;
+        self.corner_emphasis = default_settings.corner_emphasis;
+        self.ui_sounds = default_settings.ui_sounds;
+        self.roll_sound = default_settings.roll_sound;
+    }
+
+    pub fn reset_visual(&mut self, default_settings: &Settings) {
+        self.ambient_occlusion_focus = default_settings.ambient_occlusion_focus;
+        self.ambient_occlusion_strength = default_settings.ambient_occlusion_strength;
+        self.specular_focus = default_settings.specular_focus;
+        self.specular_strength = default_settings.specular_strength;
+        self.ambient_light = default_settings.ambient_light;
+        self.shadow_size = default_settings.shadow_size;
+        self.shadow_distance_strength = default_settings.shadow_distance_strength;
+        self.shadow_strength = default_settings.shadow_strength;
+        self.background_mode = default_settings.background_mode;
+        self.shadow_color = default_settings.shadow_color;
+        self.glass_overlay = default_settings.glass_overlay;
+        self.glass_overlay_intensity = default_settings.glass_overlay_intensity;
+        self.ball_blend_mode = default_settings.ball_blend_mode;
+        self.msaa_samples = default_settings.msaa_samples;
+        self.particle_count = default_settings.particle_count;
+        self.particle_intensity = default_settings.particle_intensity;
+        self.light_angle = default_settings.light_angle;
+        self.light_height = default_settings.light_height;
+        self.max_texture_size = default_settings.max_texture_size;
+        self.texture_filter = default_settings.texture_filter;
+        self.box_interior_color = default_settings.box_interior_color;
+        self.smooth_edge = default_settings.smooth_edge;
+    }
+
+    pub fn reset_box(&mut self, default_settings: &Settings) {
+        self.box_weight_x = default_settings.box_weight_x;
+        self.box_weight_y = default_settings.box_weight_y;
+        self.hide_smoothing = default_settings.hide_smoothing;
+        self.quick_turn = default_settings.quick_turn;
+        self.box_width = default_settings.box_width;
+        self.box_height = default_settings.box_height;
+        self.box_thickness = default_settings.box_thickness;
+        self.box_depth = default_settings.box_depth;
+        self.smoothing_mode = default_settings.smoothing_mode;
+        self.box_corner_radius = default_settings.box_corner_radius;
+        self.max_window_speed = default_settings.max_window_speed;
+        self.recoil_strength = default_settings.recoil_strength;
+    }
+
+    pub fn reset_physics(&mut self, default_settings: &Settings) {
+        self.gravity_strength = default_settings.gravity_strength;
+        self.air_friction = default_settings.air_friction;
+        self.max_velocity = default_settings.max_velocity;
+        self.ball_bounciness = default_settings.ball_bounciness;
+        self.ball_weight = default_settings.ball_weight;
+        self.ball_friction = default_settings.ball_friction;
+        self.drag_model = default_settings.drag_model;
+        self.throw_strength = default_settings.throw_strength;
+        self.energy_decay = default_settings.energy_decay;
+        self.weight_throw_influence = default_settings.weight_throw_influence;
+        self.floor_friction_mult = default_settings.floor_friction_mult;
+        self.wall_friction_mult = default_settings.wall_friction_mult;
+        self.bounce_falloff = default_settings.bounce_falloff;
+        self.velocity_filter = default_settings.velocity_filter;
+        self.spin_friction = default_settings.spin_friction;
+        self.gravity_gradient = default_settings.gravity_gradient;
+        self.min_bounce_speed = default_settings.min_bounce_speed;
+        self.soft_collision = default_settings.soft_collision;
+        self.soft_collision_stiffness = default_settings.soft_collision_stiffness;
+    }
+
+    pub fn reset_fps_delay(&mut self, default_settings: &Settings) {
+        self.delay_frames = default_settings.delay_frames;
+        self.max_fps = default_settings.max_fps;
+        self.vsync = default_settings.vsync;
+        self.fixed_timestep = default_settings.fixed_timestep;
+        self.max_delta_time = default_settings.max_delta_time;
+    }
+
+    pub fn reset_misc(&mut self, default_settings: &Settings) {
+        self.ball_radius = default_settings.ball_radius;
+        self.speed_mul = default_settings.speed_mul;
+        self.click_to_drag = default_settings.click_to_drag;
+        self.spawn_at_cursor = default_settings.spawn_at_cursor;
+        self.last_ball = default_settings.last_ball.clone();
+        self.last_sounds = default_settings.last_sounds.clone();
+        self.last_asset_pack = default_settings.last_asset_pack.clone();
+        self.idle_throttle = default_settings.idle_throttle;
+        self.start_monitor = default_settings.start_monitor;
+        self.random_seed = default_settings.random_seed;
+        self.free_roam = default_settings.free_roam;
+        self.ball_start_rotation = default_settings.ball_start_rotation;
+        self.flip_with_direction = default_settings.flip_with_direction;
+        self.keybindings = default_settings.keybindings.clone();
+        self.fullscreen = default_settings.fullscreen;
+        self.slowdown_factor = default_settings.slowdown_factor;
+        self.grab_ball = default_settings.grab_ball;
+        self.cursor_gravity = default_settings.cursor_gravity;
+        self.cursor_gravity_strength = default_settings.cursor_gravity_strength;
+        self.match_mode = default_settings.match_mode;
+        self.idle_bob = default_settings.idle_bob;
+        self.clickthrough_background = default_settings.clickthrough_background;
+        self.max_visible_errors = default_settings.max_visible_errors;
+        self.attract_mode = default_settings.attract_mode;
+        self.attract_delay = default_settings.attract_delay;
+        self.language = default_settings.language.clone();
+        self.magnet_wall = default_settings.magnet_wall;
+        self.lock_window = default_settings.lock_window;
+        self.cursor_hide_delay = default_settings.cursor_hide_delay;
+        self.error_display_time = default_settings.error_display_time;
+        self.open_menu_on_start = default_settings.open_menu_on_start;
     }
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
+
             audio_volume: 0.5,
             hit_density: 0.25,
             min_hit_speed: 120.,
+            max_voices: 8,
+            hit_sound_cooldown: 1.0 / 60.0,
+            volume_curve: VolumeCurve::Exponential,
+            max_hit_speed: 3000.,
+            corner_emphasis: 1.0,
+            ui_sounds: true,
+            roll_sound: true,
             gravity_strength: 3.5,
             air_friction: 0.14,
+            drag_model: DragModel::Linear,
             max_velocity: 200.,
 
             ball_bounciness: 0.9,
+            energy_decay: 0.0,
             ball_radius: 90,
             ball_weight: 0.65,
             ball_friction: 0.75,
+            floor_friction_mult: 1.0,
+            wall_friction_mult: 1.0,
+            bounce_falloff: 0.0,
+            velocity_filter: 0.0,
+            spin_friction: 0.0,
+            gravity_gradient: 0.0,
+            min_bounce_speed: 0.0,
+            soft_collision: false,
+            soft_collision_stiffness: 2000.0,
+            throw_strength: 2.,
+            weight_throw_influence: 0.0,
 
             box_width: 640,
             box_height: 480,
             box_thickness: 25,
             box_depth: 20,
+            box_corner_radius: 0,
+            max_box_width: DEFAULT_MAX_BOX_WIDTH,
+            max_box_height: DEFAULT_MAX_BOX_HEIGHT,
+            max_box_thickness: DEFAULT_MAX_BOX_THICKNESS,
 
             ambient_occlusion_focus: 1.1,
             ambient_occlusion_strength: 0.75,
@@ -324,16 +1278,63 @@ impl Default for Settings {
             shadow_size: 1.2,
             shadow_distance_strength: 0.55,
             shadow_strength: 1.1,
+            shadow_color: [0., 0., 0.],
+            box_interior_color: [0.15, 0.15, 0.15],
+            background_mode: BackgroundMode::Stretch,
+            glass_overlay: false,
+            glass_overlay_intensity: 0.5,
+            ball_blend_mode: BallBlendMode::Alpha,
+            msaa_samples: 0,
+            particle_count: 0,
+            particle_intensity: 1.0,
+            light_angle: -36.87,
+            light_height: 1.2,
+            max_texture_size: 2048,
+            texture_filter: TextureFilter::Linear,
+            smooth_edge: false,
 
             delay_frames: 0,
             max_fps: 60,
             vsync: false,
+            fixed_timestep: false,
+            max_delta_time: 1.0 / 10.0,
             speed_mul: 1.0,
 
-            box_weight: 0.02,
+            box_weight_x: 0.02,
+            box_weight_y: 0.02,
             hide_smoothing: false,
             quick_turn: true,
+            max_window_speed: 0.0,
+            recoil_strength: 0.0,
+            smoothing_mode: SmoothingMode::Critical,
             click_to_drag: true,
+            spawn_at_cursor: false,
+            idle_throttle: true,
+            free_roam: false,
+            flip_with_direction: false,
+            fullscreen: false,
+            slowdown_factor: 0.25,
+            grab_ball: false,
+            cursor_gravity: false,
+            cursor_gravity_strength: 5.0,
+            match_mode: MatchMode::Suffix,
+            idle_bob: 0.0,
+            clickthrough_background: false,
+            max_visible_errors: 3,
+            error_display_time: 3.0,
+            open_menu_on_start: false,
+            attract_mode: false,
+            attract_delay: 30.0,
+            language: DEFAULT_LANGUAGE.to_string(),
+            magnet_wall: MagnetWall::Off,
+            lock_window: false,
+            cursor_hide_delay: 0.0,
+            tutorial_move_distance: WINDOW_DISTANCE_BEFORE_UNDERSTAND,
+            keybindings: KeyBindings::default(),
+            ranges: HashMap::new(),
+            ball_start_rotation: 0.,
+            start_monitor: None,
+            random_seed: None,
 
             last_ball: "grinning".to_string(),
             last_sounds: "thud".to_string(),
@@ -345,20 +1346,244 @@ impl Default for Settings {
     }
 }
 
-pub fn read_settings_file() -> Option<Settings> {
-    let bytes = fs::read("./settings_in_a.json").ok()?;
+/// Reads the settings file without validating anything, so the values are as they are in the file.
+fn read_settings_file_raw() -> Option<DeserializeSettings> {
+    let bytes = fs::read(get_game_path("settings_in_a.json")).ok()?;
     let string = str::from_utf8(&bytes).ok()?;
-    let de_settings = DeserializeSettings::deserialize_json(string).ok()?;
+    return DeserializeSettings::deserialize_json(string).ok();
+}
+
+pub fn read_settings_file() -> Option<Settings> {
+    let mut de_settings = read_settings_file_raw()?;
 
+    let migrated = de_settings.migrate();
     let (settings, is_incomplete) = de_settings.to_settings();
 
-    if is_incomplete {
+    if is_incomplete || migrated {
         write_settings_file(&settings);
     }
 
     return Some(settings);
 }
 
+/// A setting that can be overridden with an environment variable.
+struct EnvOverride {
+    name: &'static str,
+    /// Parses the value into the setting. Returns false if it couldn't be parsed.
+    apply: fn(&mut DeserializeSettings, &str) -> bool,
+    /// Puts the setting back to the value from the file (the last argument),
+    /// if it still has the value from the override (the second argument).
+    restore: fn(&mut Settings, &Settings, &Settings),
+}
+
+macro_rules! env_override {
+    ($name:literal, $field:ident) => {
+        EnvOverride {
+            name: $name,
+            apply: |settings, value| parse_env(value, &mut settings.$field),
+            restore: |settings, overridden, file_settings| {
+                if settings.$field == overridden.$field {
+                    settings.$field = file_settings.$field.clone();
+                }
+            },
+        }
+    };
+}
+
+/// Environment variables that override a setting.
+/// They're applied before the settings get validated, so they get limited the same way as the settings file,
+/// and they're left out when the settings file gets written.
+const ENV_OVERRIDES: &[EnvOverride] = &[
+    env_override!("BALL_AUDIO_VOLUME", audio_volume),
+    env_override!("BALL_GRAVITY_STRENGTH", gravity_strength),
+    env_override!("BALL_AIR_FRICTION", air_friction),
+    env_override!("BALL_BOUNCINESS", ball_bounciness),
+    env_override!("BALL_WEIGHT", ball_weight),
+    env_override!("BALL_FRICTION", ball_friction),
+    env_override!("BALL_RADIUS", ball_radius),
+    env_override!("BALL_BOX_WIDTH", box_width),
+    env_override!("BALL_BOX_HEIGHT", box_height),
+    env_override!("BALL_MAX_FPS", max_fps),
+    env_override!("BALL_VSYNC", vsync),
+    env_override!("BALL_FIXED_TIMESTEP", fixed_timestep),
+    env_override!("BALL_SPEED_MUL", speed_mul),
+    env_override!("BALL_FULLSCREEN", fullscreen),
+    env_override!("BALL_LOCK_WINDOW", lock_window),
+    env_override!("BALL_RANDOM_SEED", random_seed),
+    env_override!("BALL_LAST_BALL", last_ball),
+    env_override!("BALL_LAST_SOUNDS", last_sounds),
+    env_override!("BALL_LAST_ASSET_PACK", last_asset_pack),
+];
+
+fn parse_env<T: FromStr>(value: &str, target: &mut Option<T>) -> bool {
+    let Ok(parsed) = value.trim().parse() else {
+        return false;
+    };
+    *target = Some(parsed);
+    return true;
+}
+
+/// Overrides settings with the environment variables in `ENV_OVERRIDES` that are set.
+///
+/// Returns an error for each variable that couldn't be used. Those are left as they were.
+pub fn apply_env_overrides(settings: &mut Settings) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if ENV_OVERRIDES
+        .iter()
+        .all(|env_override| env::var(env_override.name).is_err())
+    {
+        return errors;
+    }
+
+    // The overrides go through the same checks as the settings file, so a bad value can't break anything.
+    let Ok(mut de_settings) = DeserializeSettings::deserialize_json(&settings.serialize_json())
+    else {
+        return errors;
+    };
+
+    for env_override in ENV_OVERRIDES {
+        let Ok(value) = env::var(env_override.name) else {
+            continue;
+        };
+
+        if !(env_override.apply)(&mut de_settings, &value) {
+            errors.push(format!(
+                "Invalid value for {}: \"{value}\"",
+                env_override.name
+            ));
+        }
+    }
+
+    (*settings, _) = de_settings.to_settings();
+
+    return errors;
+}
+
+/// Returns the settings with the overridden settings put back to the values from the settings file,
+/// unless they have been changed since they were overridden.
+fn remove_env_overrides(settings: &Settings) -> Settings {
+    let mut settings = settings.clone();
+
+    if ENV_OVERRIDES
+        .iter()
+        .all(|env_override| env::var(env_override.name).is_err())
+    {
+        return settings;
+    }
+
+    let file_settings = read_settings_file_raw()
+        .map(|mut de_settings| {
+            de_settings.migrate();
+            de_settings.to_settings().0
+        })
+        .unwrap_or_default();
+    let mut overridden = file_settings.clone();
+    apply_env_overrides(&mut overridden);
+
+    for env_override in ENV_OVERRIDES {
+        (env_override.restore)(&mut settings, &overridden, &file_settings);
+    }
+
+    return settings;
+}
+
+/// Reads settings from JSON, like the settings file or settings copied to the clipboard.
+///
+/// Anything missing gets the default value.
+pub fn settings_from_json(string: &str) -> Result<Settings, String> {
+    let mut de_settings =
+        DeserializeSettings::deserialize_json(string).map_err(|err| err.to_string())?;
+    de_settings.migrate();
+    let (settings, _) = de_settings.to_settings();
+    return Ok(settings);
+}
+
 pub fn write_settings_file(settings: &Settings) {
-    let _ = fs::write("./settings_in_a.json", settings.serialize_json_pretty());
+    let _ = fs::write(
+        get_game_path("settings_in_a.json"),
+        remove_env_overrides(settings).serialize_json_pretty(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_settings_get_current_version() {
+        let mut de_settings = DeserializeSettings::deserialize_json(
+            r#"{"audio_volume": 0.25, "ball_radius": 40, "last_asset_pack": "inverted"}"#,
+        )
+        .unwrap();
+        assert!(de_settings.migrate());
+
+        let (settings, is_incomplete) = de_settings.to_settings();
+        assert!(is_incomplete);
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.audio_volume, 0.25);
+        assert_eq!(settings.ball_radius, 40);
+        assert_eq!(settings.last_asset_pack, "inverted");
+
+        // Saving and loading again keeps the values without migrating a second time.
+        let mut de_settings =
+            DeserializeSettings::deserialize_json(&settings.serialize_json()).unwrap();
+        assert!(!de_settings.migrate());
+
+        let (loaded_settings, is_incomplete) = de_settings.to_settings();
+        assert!(!is_incomplete);
+        assert_eq!(loaded_settings, settings);
+    }
+
+    #[test]
+    fn asset_pack_survives_restart() {
+        let settings_path = get_game_path("settings_in_a.json");
+        let previous_file = fs::read(&settings_path).ok();
+
+        let settings = settings_from_json(r#"{"last_asset_pack": "inverted"}"#).unwrap();
+        write_settings_file(&settings);
+        let loaded_settings = read_settings_file();
+
+        // Put back the settings file of whoever is running the tests.
+        match previous_file {
+            Some(previous_file) => fs::write(&settings_path, previous_file).unwrap(),
+            None => fs::remove_file(&settings_path).unwrap(),
+        }
+
+        assert_eq!(loaded_settings.unwrap().last_asset_pack, "inverted");
+    }
+
+    #[test]
+    fn box_weight_migrates_to_both_axes() {
+        let mut de_settings =
+            DeserializeSettings::deserialize_json(r#"{"version": 1, "box_weight": 0.05}"#).unwrap();
+        assert!(de_settings.migrate());
+
+        let (settings, _) = de_settings.to_settings();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.box_weight_x, 0.05);
+        assert_eq!(settings.box_weight_y, 0.05);
+
+        // Saving and loading again keeps the values without migrating a second time.
+        let json = settings.serialize_json();
+        assert!(!json.contains("\"box_weight\""));
+        let mut de_settings = DeserializeSettings::deserialize_json(&json).unwrap();
+        assert!(!de_settings.migrate());
+
+        let (loaded_settings, _) = de_settings.to_settings();
+        assert_eq!(loaded_settings.box_weight_x, 0.05);
+        assert_eq!(loaded_settings.box_weight_y, 0.05);
+    }
+
+    #[test]
+    fn migrating_keeps_split_box_weight() {
+        let mut de_settings =
+            DeserializeSettings::deserialize_json(r#"{"box_weight": 0.05, "box_weight_y": 0.1}"#)
+                .unwrap();
+        assert!(de_settings.migrate());
+
+        let (settings, _) = de_settings.to_settings();
+        assert_eq!(settings.box_weight_x, 0.05);
+        assert_eq!(settings.box_weight_y, 0.1);
+    }
 }
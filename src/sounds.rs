@@ -1,13 +1,83 @@
-use macroquad::audio::{load_sound_from_bytes, Sound};
+use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
 
 use std::{fs, path::PathBuf};
 
 use macroquad::rand;
 
-use crate::error_log::ErrorLogs;
+use crate::{
+    error_log::ErrorLogs,
+    paths::get_game_path,
+    settings::{MatchMode, Settings},
+};
+
+/// Sounds get played on every bounce, so anything longer than this is most likely a mistake.
+const MAX_SOUND_DURATION: f32 = 5.0;
+
+/// The name of the sound in a sound pack that gets looped while the ball rolls, without the file extension.
+const ROLL_SOUND_NAME: &str = "roll";
+
+/// The sounds from a sound pack.
+#[derive(Clone, Default)]
+pub struct SoundPack {
+    /// Played when the ball hits a wall.
+    pub hits: Vec<Sound>,
+    /// Looped while the ball rolls along a wall. Sound packs don't need to have it.
+    pub roll: Option<Sound>,
+}
+
+/// The formats the audio backend can decode.
+#[derive(Clone, Copy)]
+enum SoundFormat {
+    Wav,
+    OggVorbis,
+}
+
+/// Figures out the format from the start of the file instead of the file extension,
+/// so a renamed file doesn't get passed to a decoder that can't read it.
+fn detect_sound_format(bytes: &[u8]) -> Option<SoundFormat> {
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE" {
+        return Some(SoundFormat::Wav);
+    }
+
+    if bytes.starts_with(b"OggS") {
+        // OGG files can contain other codecs, like Opus, which can't be decoded.
+        let has_vorbis_header = bytes
+            .windows(7)
+            .take(256)
+            .any(|window| window == b"\x01vorbis");
+        if has_vorbis_header {
+            return Some(SoundFormat::OggVorbis);
+        }
+    }
+
+    None
+}
+
+/// Gives a name to formats that can't be decoded, for the error message.
+fn describe_unsupported_format(bytes: &[u8], filename: &str) -> String {
+    if bytes.starts_with(b"fLaC") {
+        return "FLAC".to_string();
+    }
+    if bytes.starts_with(b"ID3")
+        || (bytes.len() >= 2 && bytes[0] == 0xFF && bytes[1] & 0xE0 == 0xE0)
+    {
+        return "MP3".to_string();
+    }
+    if bytes.starts_with(b"OggS") {
+        return "OGG without Vorbis (like Opus)".to_string();
+    }
+    if bytes.len() >= 8 && &bytes[4..8] == b"ftyp" {
+        return "M4A/AAC".to_string();
+    }
+
+    match filename.rsplit_once('.') {
+        Some((_, extension)) => extension.to_ascii_uppercase(),
+        None => "unknown".to_string(),
+    }
+}
 
 pub fn list_available_sounds(error_logs: &mut ErrorLogs) -> Vec<(String, PathBuf)> {
-    let read_dir = match fs::read_dir("./sounds") {
+    let read_dir = match fs::read_dir(get_game_path("sounds")) {
         Ok(read_dir) => read_dir,
         Err(err) => {
             error_logs.display_error(format!("Failed to read the \"sounds\" folder: {err}"));
@@ -43,7 +113,7 @@ pub fn list_available_sounds(error_logs: &mut ErrorLogs) -> Vec<(String, PathBuf
         .collect()
 }
 
-pub async fn load_sounds(path: PathBuf, error_logs: &mut ErrorLogs) -> Vec<Sound> {
+pub async fn load_sounds(path: PathBuf, error_logs: &mut ErrorLogs) -> SoundPack {
     let lossy_path = path.to_string_lossy();
     let read_dir = match fs::read_dir(&path) {
         Ok(read_dir) => read_dir,
@@ -51,7 +121,7 @@ pub async fn load_sounds(path: PathBuf, error_logs: &mut ErrorLogs) -> Vec<Sound
             error_logs.display_error(format!(
                 "Failed to read directory: \"{lossy_path}\" when loading sounds. {err}"
             ));
-            return Vec::new();
+            return SoundPack::default();
         }
     };
 
@@ -73,56 +143,153 @@ pub async fn load_sounds(path: PathBuf, error_logs: &mut ErrorLogs) -> Vec<Sound
                 return None;
             }
 
-            let filename_lossy = entry.file_name().to_string_lossy().to_ascii_lowercase();
-
-            if !filename_lossy.ends_with(".ogg") && !filename_lossy.ends_with(".wav") {
-                error_logs.display_error(
-                    "Unsupported audio format. Please use either OGG or WAV.".to_string(),
-                );
-                return None;
-            }
+            let filename = entry.file_name().to_string_lossy().to_string();
+            let is_roll = path
+                .file_stem()
+                .is_some_and(|file_stem| file_stem == ROLL_SOUND_NAME);
 
             let bytes = match fs::read(&path) {
                 Ok(bytes) => bytes,
                 Err(err) => {
                     error_logs.display_error(format!(
-                        "Failed to read sound bytes from: \"{lossy_path}\": {err}"
+                        "Failed to read sound bytes from: \"{}\": {err}",
+                        path.to_string_lossy()
                     ));
                     return None;
                 }
             };
 
-            Some(bytes)
+            // The format is checked from the data since the decoder crashes on anything it can't read.
+            let Some(format) = detect_sound_format(&bytes) else {
+                error_logs.display_error(format!(
+                    "Unsupported audio format for \"{filename}\" ({}). Please convert it to OGG Vorbis or WAV.",
+                    describe_unsupported_format(&bytes, &filename)
+                ));
+                return None;
+            };
+
+            let duration = match format {
+                SoundFormat::Wav => get_wav_duration(&bytes),
+                SoundFormat::OggVorbis => get_ogg_duration(&bytes),
+            };
+
+            // The rolling sound gets looped, so it's fine for it to be long.
+            if let Some(duration) = duration.filter(|_| !is_roll) {
+                if duration > MAX_SOUND_DURATION {
+                    error_logs.display_error(format!(
+                        "\"{filename}\" is {duration:.1} seconds long. Sounds can be at most {MAX_SOUND_DURATION} seconds."
+                    ));
+                    return None;
+                }
+            }
+
+            Some((filename, is_roll, bytes))
         })
         .flatten()
-        .collect::<Vec<Vec<u8>>>();
+        .collect::<Vec<(String, bool, Vec<u8>)>>();
 
-    let mut sounds = Vec::with_capacity(sounds_bytes.len());
+    let mut sounds = SoundPack {
+        hits: Vec::with_capacity(sounds_bytes.len()),
+        roll: None,
+    };
 
-    for bytes in sounds_bytes {
+    for (filename, is_roll, bytes) in sounds_bytes {
         let sound = match load_sound_from_bytes(&bytes).await {
             Ok(sound) => sound,
             Err(err) => {
                 error_logs.display_error(format!(
-                    "Failed to read sound data from one of the sounds in \"{lossy_path}\": {err}"
+                    "Failed to read sound data from \"{filename}\" in \"{lossy_path}\": {err}"
                 ));
                 continue;
             }
         };
 
-        sounds.push(sound);
+        if is_roll {
+            sounds.roll = Some(sound);
+        } else {
+            sounds.hits.push(sound);
+        }
     }
 
     sounds
 }
 
-/// Returns info for a folder with sounds in which the input ends with the folders name.
+/// Reads the duration in seconds from the header of a WAV file.
+fn get_wav_duration(bytes: &[u8]) -> Option<f32> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let mut byte_rate = None;
+    let mut data_size = None;
+    let mut offset = 12;
+
+    while offset + 8 <= bytes.len() {
+        let chunk_id = &bytes[offset..offset + 4];
+        let chunk_size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().ok()?);
+        let chunk_start = offset + 8;
+
+        if chunk_id == b"fmt " && chunk_start + 12 <= bytes.len() {
+            byte_rate = Some(u32::from_le_bytes(
+                bytes[chunk_start + 8..chunk_start + 12].try_into().ok()?,
+            ));
+        } else if chunk_id == b"data" {
+            data_size = Some(chunk_size);
+        }
+
+        // Chunks are padded to an even size.
+        offset = chunk_start + chunk_size as usize + (chunk_size as usize & 1);
+    }
+
+    let byte_rate = byte_rate?;
+    if byte_rate == 0 {
+        return None;
+    }
+
+    Some(data_size? as f32 / byte_rate as f32)
+}
+
+/// Reads the duration in seconds from an OGG Vorbis file by using the sample rate and the position of the last page.
+fn get_ogg_duration(bytes: &[u8]) -> Option<f32> {
+    const VORBIS_HEADER: &[u8] = b"\x01vorbis";
+    const PAGE_HEADER: &[u8] = b"OggS";
+
+    let header_position = bytes
+        .windows(VORBIS_HEADER.len())
+        .position(|window| window == VORBIS_HEADER)?;
+    let sample_rate_position = header_position + VORBIS_HEADER.len() + 5;
+    let sample_rate = u32::from_le_bytes(
+        bytes
+            .get(sample_rate_position..sample_rate_position + 4)?
+            .try_into()
+            .ok()?,
+    );
+
+    let last_page_position = bytes
+        .windows(PAGE_HEADER.len())
+        .rposition(|window| window == PAGE_HEADER)?;
+    let granule_position = i64::from_le_bytes(
+        bytes
+            .get(last_page_position + 6..last_page_position + 14)?
+            .try_into()
+            .ok()?,
+    );
+
+    if sample_rate == 0 || granule_position < 0 {
+        return None;
+    }
+
+    Some(granule_position as f32 / sample_rate as f32)
+}
+
+/// Returns info for a folder with sounds in which the input matches the folders name.
 ///
 /// Picks the folder with the longer name.
 pub async fn find_sounds(
     current_string: &str,
+    match_mode: MatchMode,
     error_logs: &mut ErrorLogs,
-) -> Option<(String, Vec<Sound>)> {
+) -> Option<(String, SoundPack)> {
     if current_string.is_empty() {
         return None;
     }
@@ -130,7 +297,7 @@ pub async fn find_sounds(
     let mut selected_sounds: Option<(String, PathBuf)> = None;
 
     for (sounds_name, sounds_path) in list_available_sounds(error_logs) {
-        if current_string.ends_with(&sounds_name.to_ascii_lowercase()) {
+        if match_mode.matches(current_string, &sounds_name.to_ascii_lowercase()) {
             if let Some((selected_sounds_name, _)) = &selected_sounds {
                 if selected_sounds_name.len() > sounds_name.len() {
                     continue;
@@ -145,7 +312,7 @@ pub async fn find_sounds(
     return Some((sounds_name, load_sounds(sounds_path, error_logs).await));
 }
 
-pub async fn get_random_sounds(error_logs: &mut ErrorLogs) -> Option<(String, Vec<Sound>)> {
+pub async fn get_random_sounds(error_logs: &mut ErrorLogs) -> Option<(String, SoundPack)> {
     let available_sounds = list_available_sounds(error_logs);
 
     if available_sounds.is_empty() {
@@ -162,3 +329,106 @@ pub async fn get_random_sounds(error_logs: &mut ErrorLogs) -> Option<(String, Ve
 
     return Some((sounds_name, load_sounds(sounds_path, error_logs).await));
 }
+
+/// Sounds for the menu, loaded from the `click`, `open` and `close` folders inside `ui_sounds`.
+/// The folders are optional, and a missing one just means that sound doesn't play.
+pub struct UiSounds {
+    pub click: Vec<Sound>,
+    pub open: Vec<Sound>,
+    pub close: Vec<Sound>,
+}
+
+impl UiSounds {
+    pub async fn load(error_logs: &mut ErrorLogs) -> Self {
+        let ui_sounds_path = get_game_path("ui_sounds");
+
+        Self {
+            click: load_optional_sounds(ui_sounds_path.join("click"), error_logs).await,
+            open: load_optional_sounds(ui_sounds_path.join("open"), error_logs).await,
+            close: load_optional_sounds(ui_sounds_path.join("close"), error_logs).await,
+        }
+    }
+
+    /// Plays a random sound from the list, if there are any.
+    pub fn play(sounds: &[Sound], settings: &Settings) {
+        if sounds.is_empty() {
+            return;
+        }
+
+        play_sound(
+            &sounds[rand::gen_range(0, sounds.len())],
+            PlaySoundParams {
+                looped: false,
+                volume: settings.audio_volume,
+            },
+        );
+    }
+}
+
+/// Same as `load_sounds`, but a folder that doesn't exist isn't an error.
+async fn load_optional_sounds(path: PathBuf, error_logs: &mut ErrorLogs) -> Vec<Sound> {
+    if !path.is_dir() {
+        return Vec::new();
+    }
+
+    load_sounds(path, error_logs).await.hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A mono 16 bit WAV file with `data_size` bytes of silence.
+    fn wav_bytes(sample_rate: u32, data_size: u32) -> Vec<u8> {
+        let byte_rate = sample_rate * 2;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+        bytes.extend_from_slice(b"WAVE");
+        bytes.extend_from_slice(b"fmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&byte_rate.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_size.to_le_bytes());
+        bytes.resize(bytes.len() + data_size as usize, 0);
+        bytes
+    }
+
+    #[test]
+    fn short_wav_is_accepted() {
+        let bytes = wav_bytes(44100, 44100);
+
+        assert!(matches!(
+            detect_sound_format(&bytes),
+            Some(SoundFormat::Wav)
+        ));
+        let duration = get_wav_duration(&bytes).unwrap();
+        assert!((duration - 0.5).abs() < 0.001);
+        assert!(duration <= MAX_SOUND_DURATION);
+    }
+
+    #[test]
+    fn long_wav_is_rejected() {
+        let bytes = wav_bytes(8000, 8000 * 2 * 60);
+
+        assert!(matches!(
+            detect_sound_format(&bytes),
+            Some(SoundFormat::Wav)
+        ));
+        assert!(get_wav_duration(&bytes).unwrap() > MAX_SOUND_DURATION);
+    }
+
+    #[test]
+    fn non_audio_is_not_detected() {
+        assert!(detect_sound_format(b"This is a text file, not a sound.").is_none());
+        assert!(detect_sound_format(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").is_none());
+        // An OGG file with another codec than Vorbis.
+        assert!(detect_sound_format(b"OggS\0\x02\0\0\0\0\0\0\0\0OpusHead").is_none());
+        assert!(get_wav_duration(b"RIFF").is_none());
+    }
+}
@@ -1,11 +1,89 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
+use image::imageops::FilterType;
 use macroquad::{rand, texture::Texture2D};
+use nanoserde::DeJson;
 
-use crate::error_log::ErrorLogs;
+use crate::{error_log::ErrorLogs, paths::get_game_path, settings::Settings};
+
+/// Suggested settings that come with a ball, read from a JSON file with the same name as the ball.
+#[derive(Debug, DeJson, Default)]
+pub struct BallMetadata {
+    ball_radius: Option<f32>,
+    ball_bounciness: Option<f32>,
+    ball_weight: Option<f32>,
+    ball_friction: Option<f32>,
+}
+
+impl BallMetadata {
+    /// Returns the settings with the suggestions from the ball applied.
+    ///
+    /// Settings the user has changed from the default are left alone.
+    pub fn apply_to(&self, settings: &Settings) -> Settings {
+        let default_settings = Settings::default();
+        let mut settings = settings.clone();
+
+        if let Some(ball_radius) = self.ball_radius {
+            if settings.ball_radius == default_settings.ball_radius && ball_radius >= 1. {
+                settings.ball_radius = ball_radius as u32;
+            }
+        }
+        if let Some(ball_bounciness) = self.ball_bounciness {
+            if settings.ball_bounciness == default_settings.ball_bounciness {
+                settings.ball_bounciness = ball_bounciness;
+            }
+        }
+        if let Some(ball_weight) = self.ball_weight {
+            if settings.ball_weight == default_settings.ball_weight {
+                settings.ball_weight = ball_weight;
+            }
+        }
+        if let Some(ball_friction) = self.ball_friction {
+            if settings.ball_friction == default_settings.ball_friction {
+                settings.ball_friction = ball_friction;
+            }
+        }
+
+        return settings;
+    }
+}
+
+/// Loads the metadata for a ball. Balls without a metadata file get the default, which changes nothing.
+pub fn load_ball_metadata(ball_name: &str, error_logs: &mut ErrorLogs) -> BallMetadata {
+    let path = get_game_path("balls").join(format!("{ball_name}.json"));
+
+    if !path.is_file() {
+        return BallMetadata::default();
+    }
+
+    let string = match fs::read_to_string(&path) {
+        Ok(string) => string,
+        Err(err) => {
+            error_logs.display_error(format!(
+                "Failed to read ball metadata from \"{}\": {err}",
+                path.to_string_lossy()
+            ));
+            return BallMetadata::default();
+        }
+    };
+
+    match BallMetadata::deserialize_json(&string) {
+        Ok(metadata) => return metadata,
+        Err(err) => {
+            error_logs.display_error(format!(
+                "Failed to parse ball metadata from \"{}\": {err}",
+                path.to_string_lossy()
+            ));
+            return BallMetadata::default();
+        }
+    }
+}
 
 pub fn list_available_balls(error_logs: &mut ErrorLogs) -> Vec<(String, PathBuf)> {
-    let read_dir = match fs::read_dir("./balls") {
+    let read_dir = match fs::read_dir(get_game_path("balls")) {
         Ok(read_dir) => read_dir,
         Err(err) => {
             error_logs.display_error(format!("Failed to read the \"balls\" folder: {err}"));
@@ -31,6 +109,11 @@ pub fn list_available_balls(error_logs: &mut ErrorLogs) -> Vec<(String, PathBuf)
 
             let filename_str = filename.to_string_lossy();
 
+            // Metadata files for the balls.
+            if filename_str.to_ascii_lowercase().ends_with(".json") {
+                return None;
+            }
+
             if !filename_str.to_ascii_lowercase().ends_with(".png") {
                 error_logs.display_error(
                     "Image with unsupported format found. Please use PNG.".to_string(),
@@ -48,11 +131,59 @@ pub fn list_available_balls(error_logs: &mut ErrorLogs) -> Vec<(String, PathBuf)
         .collect()
 }
 
-/// Returns info for a ball texture in which the input ends with its name.
+/// Loads a ball texture with the texture filter from the settings.
+///
+/// Images bigger than `max_texture_size` get scaled down so the biggest side is `max_texture_size`.
+pub fn load_ball_texture(
+    ball_path: &Path,
+    settings: &Settings,
+    error_logs: &mut ErrorLogs,
+) -> Option<Texture2D> {
+    let max_size = settings.max_texture_size;
+
+    let bytes = match fs::read(ball_path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            error_logs.display_error(format!(
+                "Failed to read texture bytes from \"{}\": {err}",
+                ball_path.to_string_lossy()
+            ));
+            return None;
+        }
+    };
+
+    let image = match image::load_from_memory(&bytes) {
+        Ok(image) => image,
+        Err(err) => {
+            error_logs.display_error(format!(
+                "Failed to read texture data from \"{}\": {err}",
+                ball_path.to_string_lossy()
+            ));
+            return None;
+        }
+    };
+
+    // Keeps the aspect ratio, so only the biggest side ends up being `max_size`.
+    let image = if image.width() > max_size || image.height() > max_size {
+        image.resize(max_size, max_size, FilterType::Triangle)
+    } else {
+        image
+    };
+
+    let image = image.to_rgba8();
+
+    let texture = Texture2D::from_rgba8(image.width() as u16, image.height() as u16, &image);
+    texture.set_filter(settings.texture_filter.filter_mode());
+
+    return Some(texture);
+}
+
+/// Returns info for a ball texture in which the input matches its name.
 ///
 /// Picks the texture with the longer name.
 pub fn find_texture(
     current_string: &str,
+    settings: &Settings,
     error_logs: &mut ErrorLogs,
 ) -> Option<(String, Texture2D)> {
     if current_string.is_empty() {
@@ -62,7 +193,10 @@ pub fn find_texture(
     let mut selected_ball: Option<(String, PathBuf)> = None;
 
     for (ball_name, ball_path) in list_available_balls(error_logs) {
-        if current_string.ends_with(&ball_name.to_ascii_lowercase()) {
+        if settings
+            .match_mode
+            .matches(current_string, &ball_name.to_ascii_lowercase())
+        {
             if let Some((selected_ball_name, _)) = &selected_ball {
                 if selected_ball_name.len() > ball_name.len() {
                     continue;
@@ -74,32 +208,15 @@ pub fn find_texture(
 
     let (ball_name, ball_path) = selected_ball?;
 
-    let bytes = match fs::read(&ball_path) {
-        Ok(bytes) => bytes,
-        Err(err) => {
-            error_logs.display_error(format!(
-                "Failed to read texture bytes from \"{}\": {err}",
-                ball_path.to_string_lossy()
-            ));
-            return None;
-        }
-    };
-
-    let ball_texture = match Texture2D::from_file_with_format(&bytes, None) {
-        Ok(texture) => texture,
-        Err(err) => {
-            error_logs.display_error(format!(
-                "Failed to read texture data from \"{}\": {err}",
-                ball_path.to_string_lossy()
-            ));
-            return None;
-        }
-    };
+    let ball_texture = load_ball_texture(&ball_path, settings, error_logs)?;
 
     return Some((ball_name, ball_texture));
 }
 
-pub fn get_random_texture(error_logs: &mut ErrorLogs) -> Option<(String, Texture2D)> {
+pub fn get_random_texture(
+    settings: &Settings,
+    error_logs: &mut ErrorLogs,
+) -> Option<(String, Texture2D)> {
     let available_balls = list_available_balls(error_logs);
 
     if available_balls.is_empty() {
@@ -114,27 +231,7 @@ pub fn get_random_texture(error_logs: &mut ErrorLogs) -> Option<(String, Texture
             .unwrap_unchecked()
     };
 
-    let bytes = match fs::read(&ball_path) {
-        Ok(bytes) => bytes,
-        Err(err) => {
-            error_logs.display_error(format!(
-                "Failed to read texture bytes from \"{}\": {err}",
-                ball_path.to_string_lossy()
-            ));
-            return None;
-        }
-    };
-
-    let ball_texture = match Texture2D::from_file_with_format(&bytes, None) {
-        Ok(texture) => texture,
-        Err(err) => {
-            error_logs.display_error(format!(
-                "Failed to read texture data from \"{}\": {err}",
-                ball_path.to_string_lossy()
-            ));
-            return None;
-        }
-    };
+    let ball_texture = load_ball_texture(&ball_path, settings, error_logs)?;
 
     return Some((ball_name, ball_texture));
 }
@@ -1,6 +1,31 @@
 use macroquad::prelude::*;
 
-use crate::assets::GameAssets;
+use crate::{assets::GameAssets, keys::key_code_name};
+
+/// The box size the cursor sizes were made for.
+const RELATIVE_BOX_SIZE: Vec2 = vec2(640., 480.);
+
+const CURSOR_SIZE: f32 = 150.;
+const SPACING: f32 = 5.;
+
+/// How much the tutorial should be scaled so it stays proportional to the box.
+fn get_tutorial_scale(box_size: Vec2) -> f32 {
+    return (box_size / RELATIVE_BOX_SIZE).min_element();
+}
+
+/// Starts and ends slowly, like the box does when it's moved. Goes from 0 to 1 like the input,
+/// so the movement still starts and ends at the same time as without it.
+fn ease_in_out(t: f32) -> f32 {
+    let t = t.clamp(0., 1.);
+    return t * t * (3. - 2. * t);
+}
+
+fn get_draw_texture_params(cursor_size: f32) -> DrawTextureParams {
+    DrawTextureParams {
+        dest_size: Some(vec2(cursor_size, cursor_size)),
+        ..Default::default()
+    }
+}
 
 pub fn render_mouse_tutorial(
     game_assets: &GameAssets,
@@ -15,19 +40,10 @@ pub fn render_mouse_tutorial(
     const MOVE_DURATION: f32 = 1.2;
     const CYCLE_DURATION: f32 = MOVE_DURATION + FADE_TIME * 2. + CLICK_TIME * 2.;
 
-    const CURSOR_SIZE: f32 = 150.;
-    const CURSOR_HALF_SIZE: f32 = -CURSOR_SIZE / 2.;
-
-    const SPACING: f32 = 5.;
-
-    const DRAW_TEXTURE_PARAMS: DrawTextureParams = DrawTextureParams {
-        dest_size: Some(vec2(CURSOR_SIZE, CURSOR_SIZE)),
-        source: None,
-        rotation: 0.,
-        flip_x: false,
-        flip_y: false,
-        pivot: None,
-    };
+    let scale = get_tutorial_scale(box_size);
+    let cursor_size = CURSOR_SIZE * scale;
+    let cursor_half_size = -cursor_size / 2.;
+    let spacing = SPACING * scale;
 
     let cycle_time = time % CYCLE_DURATION;
 
@@ -66,7 +82,7 @@ pub fn render_mouse_tutorial(
         let time_since = cycle_time - FADE_TIME - CLICK_TIME;
         let move_precentage = time_since / MOVE_DURATION;
         alpha = 1.0;
-        y_pos = start_y.lerp(end_y, move_precentage);
+        y_pos = start_y.lerp(end_y, ease_in_out(move_precentage));
         right_texture = &game_assets.mouse_normal_move;
         left_texture = &game_assets.mouse_hold_move;
     } else if cycle_time - FADE_TIME - CLICK_TIME - MOVE_DURATION < CLICK_TIME {
@@ -89,49 +105,43 @@ pub fn render_mouse_tutorial(
 
     draw_texture_ex(
         right_texture,
-        SPACING,
-        y_pos + CURSOR_HALF_SIZE,
+        spacing,
+        y_pos + cursor_half_size,
         Color::new(1., 1., 1., alpha),
-        DRAW_TEXTURE_PARAMS,
+        get_draw_texture_params(cursor_size),
     );
     draw_texture_ex(
         left_texture,
-        -CURSOR_SIZE - SPACING,
-        y_pos + CURSOR_HALF_SIZE,
+        -cursor_size - spacing,
+        y_pos + cursor_half_size,
         Color::new(1., 1., 1., alpha),
-        DRAW_TEXTURE_PARAMS,
+        get_draw_texture_params(cursor_size),
     );
     draw_texture_ex(
         &game_assets.slash,
-        -CURSOR_SIZE / 2.,
-        y_pos + CURSOR_HALF_SIZE,
+        -cursor_size / 2.,
+        y_pos + cursor_half_size,
         Color::new(1., 1., 1., alpha),
-        DRAW_TEXTURE_PARAMS,
+        get_draw_texture_params(cursor_size),
     );
 }
 
-pub fn render_menu_tutorial(game_assets: &GameAssets, time: f32) {
+pub fn render_menu_tutorial(game_assets: &GameAssets, time: f32, box_size: Vec2) {
     const CLICK_TIME: f32 = 0.175;
     const WAIT_TIME: f32 = 1.25;
     const FADE_IN_SPEED: f32 = 2.0;
     const CYCLE_DURATION: f32 = WAIT_TIME + CLICK_TIME * 3.;
 
-    const CURSOR_SIZE: f32 = 150.;
-    const CURSOR_HALF_SIZE: f32 = -CURSOR_SIZE / 2.;
     const ESC_DOWN_OFFSET: f32 = 10.;
 
     const ESC_LEFT_OFFSET: f32 = 15.;
 
-    const SPACING: f32 = 5.;
-
-    const DRAW_TEXTURE_PARAMS: DrawTextureParams = DrawTextureParams {
-        dest_size: Some(vec2(CURSOR_SIZE, CURSOR_SIZE)),
-        source: None,
-        rotation: 0.,
-        flip_x: false,
-        flip_y: false,
-        pivot: None,
-    };
+    let scale = get_tutorial_scale(box_size);
+    let cursor_size = CURSOR_SIZE * scale;
+    let cursor_half_size = -cursor_size / 2.;
+    let spacing = SPACING * scale;
+    let esc_down_offset = ESC_DOWN_OFFSET * scale;
+    let esc_left_offset = ESC_LEFT_OFFSET * scale;
 
     let alpha = (time * FADE_IN_SPEED).min(1.);
 
@@ -140,70 +150,114 @@ pub fn render_menu_tutorial(game_assets: &GameAssets, time: f32) {
     if cycle_time < WAIT_TIME {
         draw_texture_ex(
             &game_assets.mouse_normal,
-            SPACING,
-            CURSOR_HALF_SIZE,
+            spacing,
+            cursor_half_size,
             Color::new(1., 1., 1., alpha),
-            DRAW_TEXTURE_PARAMS,
+            get_draw_texture_params(cursor_size),
         );
         draw_texture_ex(
             &game_assets.esc_normal,
-            -CURSOR_SIZE - SPACING - ESC_LEFT_OFFSET,
-            CURSOR_HALF_SIZE,
+            -cursor_size - spacing - esc_left_offset,
+            cursor_half_size,
             Color::new(1., 1., 1., alpha),
-            DRAW_TEXTURE_PARAMS,
+            get_draw_texture_params(cursor_size),
         );
     } else if cycle_time < WAIT_TIME + CLICK_TIME {
         draw_texture_ex(
             &game_assets.mouse_hold,
-            SPACING,
-            CURSOR_HALF_SIZE,
+            spacing,
+            cursor_half_size,
             WHITE,
-            DRAW_TEXTURE_PARAMS,
+            get_draw_texture_params(cursor_size),
         );
         draw_texture_ex(
             &game_assets.esc_hold,
-            -CURSOR_SIZE - SPACING - ESC_LEFT_OFFSET,
-            ESC_DOWN_OFFSET + CURSOR_HALF_SIZE,
+            -cursor_size - spacing - esc_left_offset,
+            esc_down_offset + cursor_half_size,
             WHITE,
-            DRAW_TEXTURE_PARAMS,
+            get_draw_texture_params(cursor_size),
         );
     } else if cycle_time < WAIT_TIME + CLICK_TIME * 2. {
         draw_texture_ex(
             &game_assets.mouse_normal,
-            SPACING,
-            CURSOR_HALF_SIZE,
+            spacing,
+            cursor_half_size,
             WHITE,
-            DRAW_TEXTURE_PARAMS,
+            get_draw_texture_params(cursor_size),
         );
         draw_texture_ex(
             &game_assets.esc_hold,
-            -CURSOR_SIZE - SPACING - ESC_LEFT_OFFSET,
-            ESC_DOWN_OFFSET + CURSOR_HALF_SIZE,
+            -cursor_size - spacing - esc_left_offset,
+            esc_down_offset + cursor_half_size,
             WHITE,
-            DRAW_TEXTURE_PARAMS,
+            get_draw_texture_params(cursor_size),
         );
     } else if cycle_time < WAIT_TIME + CLICK_TIME * 3. {
         draw_texture_ex(
             &game_assets.mouse_hold,
-            SPACING,
-            CURSOR_HALF_SIZE,
+            spacing,
+            cursor_half_size,
             WHITE,
-            DRAW_TEXTURE_PARAMS,
+            get_draw_texture_params(cursor_size),
         );
         draw_texture_ex(
             &game_assets.esc_hold,
-            -CURSOR_SIZE - SPACING - ESC_LEFT_OFFSET,
-            ESC_DOWN_OFFSET + CURSOR_HALF_SIZE,
+            -cursor_size - spacing - esc_left_offset,
+            esc_down_offset + cursor_half_size,
             WHITE,
-            DRAW_TEXTURE_PARAMS,
+            get_draw_texture_params(cursor_size),
         );
     }
 
     draw_texture_ex(
         &game_assets.slash,
-        -CURSOR_SIZE / 2.,
-        CURSOR_HALF_SIZE,
+        -cursor_size / 2.,
+        cursor_half_size,
         Color::new(1., 1., 1., alpha),
-        DRAW_TEXTURE_PARAMS,
+        get_draw_texture_params(cursor_size),
+    );
+}
+
+/// A short text hint at the bottom of the box for finding the menu, shown when the game starts.
+///
+/// `open_menu_key` is the key bound to opening the menu, so the hint shows the right key.
+pub fn render_menu_hint(
+    game_assets: &GameAssets,
+    time: f32,
+    box_size: Vec2,
+    open_menu_key: KeyCode,
+) {
+    const SHOW_TIME: f32 = 5.;
+    const FADE_TIME: f32 = 1.;
+    const MAX_ALPHA: f32 = 0.7;
+    const FONT_SIZE: u16 = 16;
+    /// How far up from the bottom of the box the text is, relative to the box height.
+    const BOTTOM_OFFSET: f32 = 0.15;
+
+    if time > SHOW_TIME + FADE_TIME * 2. {
+        return;
+    }
+
+    let alpha = (time / FADE_TIME).min((SHOW_TIME + FADE_TIME * 2. - time) / FADE_TIME);
+    let alpha = alpha.clamp(0., 1.) * MAX_ALPHA;
+
+    let scale = get_tutorial_scale(box_size);
+    let text = game_assets
+        .translations
+        .get("Double-click or {key} for menu")
+        .replace("{key}", key_code_name(open_menu_key));
+    let size = measure_text(&text, Some(&game_assets.font), FONT_SIZE, 2. * scale);
+
+    draw_text_ex(
+        &text,
+        -size.width / 2.,
+        box_size.y * (1. - BOTTOM_OFFSET),
+        TextParams {
+            color: Color::new(1., 1., 1., alpha),
+            font: Some(&game_assets.font),
+            font_size: FONT_SIZE,
+            font_scale: 2. * scale,
+            ..Default::default()
+        },
     );
 }
@@ -4,9 +4,21 @@ use macroquad::{prelude::*, ui::hash};
 use miniquad::*;
 use window::{order_quit, set_mouse_cursor};
 
-use crate::{assets::GameAssets, Settings, FPS_LIMIT};
+use crate::{
+    assets::GameAssets,
+    error_log::ERROR_MAX_COUNT,
+    settings::{
+        BackgroundMode, BallBlendMode, DragModel, MagnetWall, MatchMode, SmoothingMode,
+        TextureFilter, VolumeCurve, MIN_BOX_SIZE, MIN_MAX_DELTA_TIME, MIN_TEXTURE_SIZE,
+    },
+    Settings, FPS_LIMIT,
+};
 
 const RELATIVE_BOX_SIZE: Vec2 = vec2(372., 450.);
+/// Below this scale the menu gets hard to read, so it starts growing to fill the whole box instead of leaving a border around it.
+const COMPACT_MULT: f32 = 0.8;
+/// How far below `COMPACT_MULT` the scale has to go before the menu fills the whole box.
+const COMPACT_FADE: f32 = 0.2;
 
 pub const MENU_SIZE: Vec2 = vec2(310., 400.);
 const BUTTON_SIZE: Vec2 = vec2(160., 75.);
@@ -21,6 +33,11 @@ const ACTIVE_TEXT_COLOR: Color = Color::new(0.3, 0., 0.6, 1.);
 const CHANGED_TEXT_COLOR: Color = Color::new(0.2, 0., 0.4, 1.);
 const DARKRED_TEXT_COLOR: Color = Color::new(0.3, 0., 0.0, 1.);
 
+/// How much slower the slider moves compared to the mouse when holding shift.
+const FINE_SLIDER_SCALE: f32 = 0.1;
+/// How much bigger the scroll wheel steps are when holding shift.
+const BIG_SCROLL_STEP_MULT: f32 = 10.;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum SettingsState {
     Closed,
@@ -50,12 +67,12 @@ impl SettingsState {
     // Returns the current page and the last available page index.
     pub fn get_page_info_mut(&mut self) -> Option<(&mut u8, u8)> {
         match self {
-            SettingsState::Audio(page) => Some((page, 0)),
-            SettingsState::Visuals(page) => Some((page, 1)),
-            SettingsState::Box(page) => Some((page, 1)),
-            SettingsState::Physics(page) => Some((page, 1)),
-            SettingsState::FpsDelay(page) => Some((page, 0)),
-            SettingsState::Misc(page) => Some((page, 0)),
+            SettingsState::Audio(page) => Some((page, 3)),
+            SettingsState::Visuals(page) => Some((page, 7)),
+            SettingsState::Box(page) => Some((page, 2)),
+            SettingsState::Physics(page) => Some((page, 6)),
+            SettingsState::FpsDelay(page) => Some((page, 1)),
+            SettingsState::Misc(page) => Some((page, 8)),
             _ => None,
         }
     }
@@ -74,10 +91,22 @@ pub struct UiRenderer {
     pub user_input: String,
     pub mult: f32,
     pub reset_field: bool,
+    /// If the last apply can be undone. The apply button turns into an undo button when nothing has been changed.
+    pub can_undo: bool,
     default_settings: Settings,
     slider_follow: bool,
+    /// The mouse x position and slider value from when fine adjustment started.
+    fine_slider_anchor: Option<(f32, f32)>,
     active_id: u64,
     interacted: bool,
+    undo: bool,
+    copy_settings: bool,
+    paste_settings: bool,
+    clicked_button: bool,
+    focused_index: Option<usize>,
+    focusable_count: usize,
+    focus_activated: bool,
+    focus_adjust: f32,
 }
 
 pub fn get_changed_color(changed: bool) -> Color {
@@ -102,21 +131,99 @@ impl UiRenderer {
             user_input: String::new(),
             mult: 1.,
             slider_follow: false,
+            fine_slider_anchor: None,
             reset_field: false,
+            can_undo: false,
             default_settings: Settings::default(),
             active_id: 0,
             interacted: false,
+            undo: false,
+            copy_settings: false,
+            paste_settings: false,
+            clicked_button: false,
+            focused_index: None,
+            focusable_count: 0,
+            focus_activated: false,
+            focus_adjust: 0.,
         }
     }
 
+    /// If a control is active, so typed keys go to it instead of the game.
+    pub fn is_typing(&self) -> bool {
+        self.active_id != 0
+    }
+
     pub fn reset_focused(&mut self) {
         self.active_id = 0;
+        self.focused_index = None;
+    }
+
+    /// Handles moving the keyboard focus between controls.
+    ///
+    /// Controls are counted in the order they get rendered, so the count from the previous frame is used for wrapping around.
+    fn update_keyboard_focus(&mut self) {
+        let last_focusable_count = self.focusable_count;
+        self.focusable_count = 0;
+
+        let shift_is_down = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let tab_is_pressed = is_key_pressed(KeyCode::Tab);
+
+        let focus_next = is_key_pressed(KeyCode::Down) || tab_is_pressed && !shift_is_down;
+        let focus_prev = is_key_pressed(KeyCode::Up) || tab_is_pressed && shift_is_down;
+
+        if (focus_next || focus_prev) && last_focusable_count != 0 {
+            self.focused_index = Some(match self.focused_index {
+                Some(index) if focus_next => (index + 1) % last_focusable_count,
+                Some(index) => (index + last_focusable_count - 1) % last_focusable_count,
+                None if focus_next => 0,
+                None => last_focusable_count - 1,
+            });
+            self.active_id = 0;
+            self.user_input = String::new();
+        }
+
+        self.focus_activated = self.focused_index.is_some() && is_key_pressed(KeyCode::Enter);
+
+        self.focus_adjust = 0.;
+        if is_key_pressed(KeyCode::Right) {
+            self.focus_adjust += 1.;
+        }
+        if is_key_pressed(KeyCode::Left) {
+            self.focus_adjust -= 1.;
+        }
+    }
+
+    /// Registers a control that can be focused with the keyboard. Returns true if it is the focused one.
+    fn next_focusable(&mut self) -> bool {
+        let index = self.focusable_count;
+        self.focusable_count += 1;
+        self.focused_index == Some(index)
     }
 
     pub fn did_interact(&self) -> bool {
         self.interacted
     }
 
+    /// If the undo button was pressed this frame.
+    pub fn did_undo(&self) -> bool {
+        self.undo
+    }
+
+    /// If the button for copying the settings to the clipboard was pressed this frame.
+    pub fn did_copy_settings(&self) -> bool {
+        self.copy_settings
+    }
+
+    /// If the button for pasting settings from the clipboard was pressed this frame.
+    pub fn did_paste_settings(&self) -> bool {
+        self.paste_settings
+    }
+
+    /// If any button was clicked this frame.
+    pub fn did_click_button(&self) -> bool {
+        self.clicked_button
+    }
+
     pub fn render_ui(
         &mut self,
         game_assets: &GameAssets,
@@ -127,12 +234,18 @@ impl UiRenderer {
         box_size: Vec2,
     ) -> bool {
         self.interacted = false;
+        self.clicked_button = false;
         if *settings_state == SettingsState::Closed {
             return false;
         }
 
-        let mult = box_size / RELATIVE_BOX_SIZE;
-        self.mult = mult.min_element();
+        let mult = (box_size / RELATIVE_BOX_SIZE).min_element();
+        let fill_mult = (box_size / MENU_SIZE).min_element();
+        // Grows gradually so the menu doesn't jump in size while resizing the box.
+        let compact_amount = ((COMPACT_MULT - mult) / COMPACT_FADE).clamp(0., 1.);
+        self.mult = mult + (fill_mult - mult) * compact_amount;
+
+        self.update_keyboard_focus();
 
         let mouse_pos = mouse_pos * 2. - box_size;
 
@@ -145,6 +258,9 @@ impl UiRenderer {
         );
 
         let mut save = false;
+        self.undo = false;
+        self.copy_settings = false;
+        self.paste_settings = false;
 
         let menu_position = -MENU_SIZE;
 
@@ -239,7 +355,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Audio volume",
                                 TITLE_SIZE,
-                                0.0..1.0,
+                                current_settings.slider_range("audio_volume", 0.0..1.0),
+                                2,
                                 self.default_settings.audio_volume,
                                 current_settings.audio_volume,
                                 &mut editing_settings.audio_volume,
@@ -253,7 +370,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Hit density",
                                 TITLE_SIZE,
-                                0.0..1.0,
+                                current_settings.slider_range("hit_density", 0.0..1.0),
+                                2,
                                 self.default_settings.hit_density,
                                 current_settings.hit_density,
                                 &mut editing_settings.hit_density,
@@ -267,12 +385,115 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Minimum hit speed",
                                 TITLE_SIZE,
-                                0.0..500.0,
+                                current_settings.slider_range("min_hit_speed", 0.0..500.0),
+                                0,
                                 self.default_settings.min_hit_speed,
                                 current_settings.min_hit_speed,
                                 &mut editing_settings.min_hit_speed,
                             );
                         }
+                        1 => {
+                            self.render_slider_uint(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.3),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Max voices",
+                                TITLE_SIZE,
+                                1..16,
+                                self.default_settings.max_voices,
+                                current_settings.max_voices,
+                                &mut editing_settings.max_voices,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.5),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Hit sound cooldown",
+                                TITLE_SIZE,
+                                current_settings.slider_range("hit_sound_cooldown", 0.0..0.2),
+                                3,
+                                self.default_settings.hit_sound_cooldown,
+                                current_settings.hit_sound_cooldown,
+                                &mut editing_settings.hit_sound_cooldown,
+                            );
+
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.7),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "UI sounds:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.ui_sounds,
+                                &mut editing_settings.ui_sounds,
+                            );
+                        }
+                        2 => {
+                            self.render_selector(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Volume curve:",
+                                TOGGLE_TEXT_SIZE,
+                                &[
+                                    (VolumeCurve::Exponential, "Exponential"),
+                                    (VolumeCurve::Linear, "Linear"),
+                                ],
+                                current_settings.volume_curve,
+                                &mut editing_settings.volume_curve,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.2),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Max hit speed",
+                                TITLE_SIZE,
+                                current_settings.slider_range("max_hit_speed", 0.0..10000.0),
+                                0,
+                                self.default_settings.max_hit_speed,
+                                current_settings.max_hit_speed,
+                                &mut editing_settings.max_hit_speed,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.2),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Corner emphasis",
+                                TITLE_SIZE,
+                                current_settings.slider_range("corner_emphasis", 0.0..5.0),
+                                2,
+                                self.default_settings.corner_emphasis,
+                                current_settings.corner_emphasis,
+                                &mut editing_settings.corner_emphasis,
+                            );
+                        }
+                        3 => {
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Rolling sound:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.roll_sound,
+                                &mut editing_settings.roll_sound,
+                            );
+                        }
                         _ => unreachable!(),
                     },
                     SettingsState::Visuals(page) => match *page {
@@ -285,7 +506,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "AO focus",
                                 TITLE_SIZE,
-                                0.0..5.0,
+                                current_settings.slider_range("ambient_occlusion_focus", 0.0..5.0),
+                                2,
                                 self.default_settings.ambient_occlusion_focus,
                                 current_settings.ambient_occlusion_focus,
                                 &mut editing_settings.ambient_occlusion_focus,
@@ -299,7 +521,9 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "AO strength",
                                 TITLE_SIZE,
-                                0.0..5.0,
+                                current_settings
+                                    .slider_range("ambient_occlusion_strength", 0.0..5.0),
+                                2,
                                 self.default_settings.ambient_occlusion_strength,
                                 current_settings.ambient_occlusion_strength,
                                 &mut editing_settings.ambient_occlusion_strength,
@@ -313,7 +537,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Specular focus",
                                 TITLE_SIZE,
-                                0.0..100.0,
+                                current_settings.slider_range("specular_focus", 0.0..100.0),
+                                1,
                                 self.default_settings.specular_focus,
                                 current_settings.specular_focus,
                                 &mut editing_settings.specular_focus,
@@ -327,7 +552,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Specular strength",
                                 TITLE_SIZE,
-                                0.0..10.0,
+                                current_settings.slider_range("specular_strength", 0.0..10.0),
+                                2,
                                 self.default_settings.specular_strength,
                                 current_settings.specular_strength,
                                 &mut editing_settings.specular_strength,
@@ -342,7 +568,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Ambient light",
                                 TITLE_SIZE,
-                                0.0..1.0,
+                                current_settings.slider_range("ambient_light", 0.0..1.0),
+                                2,
                                 self.default_settings.ambient_light,
                                 current_settings.ambient_light,
                                 &mut editing_settings.ambient_light,
@@ -356,7 +583,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Shadow size",
                                 TITLE_SIZE,
-                                0.0..10.0,
+                                current_settings.slider_range("shadow_size", 0.0..10.0),
+                                2,
                                 self.default_settings.shadow_size,
                                 current_settings.shadow_size,
                                 &mut editing_settings.shadow_size,
@@ -370,7 +598,9 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Shadow dist strength",
                                 TITLE_SIZE - 2,
-                                0.0..10.0,
+                                current_settings
+                                    .slider_range("shadow_distance_strength", 0.0..10.0),
+                                2,
                                 self.default_settings.shadow_distance_strength,
                                 current_settings.shadow_distance_strength,
                                 &mut editing_settings.shadow_distance_strength,
@@ -384,12 +614,262 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Shadow strength",
                                 TITLE_SIZE,
-                                0.0..10.0,
+                                current_settings.slider_range("shadow_strength", 0.0..10.0),
+                                2,
                                 self.default_settings.shadow_strength,
                                 current_settings.shadow_strength,
                                 &mut editing_settings.shadow_strength,
                             );
                         }
+                        2 => {
+                            self.render_selector(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Background:",
+                                TOGGLE_TEXT_SIZE,
+                                &[
+                                    (BackgroundMode::Stretch, "Stretch"),
+                                    (BackgroundMode::Tile, "Tile"),
+                                ],
+                                current_settings.background_mode,
+                                &mut editing_settings.background_mode,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Shadow red",
+                                TITLE_SIZE,
+                                0.0..1.0,
+                                2,
+                                self.default_settings.shadow_color[0],
+                                current_settings.shadow_color[0],
+                                &mut editing_settings.shadow_color[0],
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Shadow green",
+                                TITLE_SIZE,
+                                0.0..1.0,
+                                2,
+                                self.default_settings.shadow_color[1],
+                                current_settings.shadow_color[1],
+                                &mut editing_settings.shadow_color[1],
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 3.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Shadow blue",
+                                TITLE_SIZE,
+                                0.0..1.0,
+                                2,
+                                self.default_settings.shadow_color[2],
+                                current_settings.shadow_color[2],
+                                &mut editing_settings.shadow_color[2],
+                            );
+                        }
+                        3 => {
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Glass overlay:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.glass_overlay,
+                                &mut editing_settings.glass_overlay,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Glass intensity",
+                                TITLE_SIZE,
+                                current_settings.slider_range("glass_overlay_intensity", 0.0..1.0),
+                                2,
+                                self.default_settings.glass_overlay_intensity,
+                                current_settings.glass_overlay_intensity,
+                                &mut editing_settings.glass_overlay_intensity,
+                            );
+
+                            self.render_selector(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Ball blend:",
+                                TOGGLE_TEXT_SIZE,
+                                &[
+                                    (BallBlendMode::Alpha, "Alpha"),
+                                    (BallBlendMode::Additive, "Additive"),
+                                    (BallBlendMode::Multiply, "Multiply"),
+                                ],
+                                current_settings.ball_blend_mode,
+                                &mut editing_settings.ball_blend_mode,
+                            );
+                        }
+                        4 => {
+                            self.render_selector(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Anti-aliasing:",
+                                TOGGLE_TEXT_SIZE,
+                                &[(0, "Off"), (2, "2x"), (4, "4x"), (8, "8x")],
+                                current_settings.msaa_samples,
+                                &mut editing_settings.msaa_samples,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 0.3),
+                                "Anti-aliasing changes are used",
+                                16,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 0.6),
+                                "after restarting the game.",
+                                16,
+                            );
+                        }
+                        5 => {
+                            self.render_slider_uint(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Hit particles",
+                                TITLE_SIZE,
+                                0..20,
+                                self.default_settings.particle_count,
+                                current_settings.particle_count,
+                                &mut editing_settings.particle_count,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Particle intensity",
+                                TITLE_SIZE,
+                                current_settings.slider_range("particle_intensity", 0.0..2.0),
+                                2,
+                                self.default_settings.particle_intensity,
+                                current_settings.particle_intensity,
+                                &mut editing_settings.particle_intensity,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Light angle",
+                                TITLE_SIZE,
+                                current_settings.slider_range("light_angle", -180.0..180.0),
+                                0,
+                                self.default_settings.light_angle,
+                                current_settings.light_angle,
+                                &mut editing_settings.light_angle,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 3.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Light height",
+                                TITLE_SIZE,
+                                current_settings.slider_range("light_height", 0.0..3.0),
+                                2,
+                                self.default_settings.light_height,
+                                current_settings.light_height,
+                                &mut editing_settings.light_height,
+                            );
+                        }
+                        6 => {
+                            self.render_slider_uint(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Max texture size",
+                                TITLE_SIZE,
+                                MIN_TEXTURE_SIZE..4096,
+                                self.default_settings.max_texture_size,
+                                current_settings.max_texture_size,
+                                &mut editing_settings.max_texture_size,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 0.3),
+                                "Bigger ball images get scaled",
+                                16,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 0.6),
+                                "down to save memory.",
+                                16,
+                            );
+
+                            self.render_selector(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.2),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Texture filter:",
+                                TOGGLE_TEXT_SIZE,
+                                &[
+                                    (TextureFilter::Linear, "Smooth"),
+                                    (TextureFilter::Nearest, "Pixelated"),
+                                ],
+                                current_settings.texture_filter,
+                                &mut editing_settings.texture_filter,
+                            );
+                        }
+                        7 => {
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Smooth edge:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.smooth_edge,
+                                &mut editing_settings.smooth_edge,
+                            );
+                        }
                         _ => unreachable!(),
                     },
                     SettingsState::Box(page) => match *page {
@@ -398,21 +878,37 @@ impl UiRenderer {
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., start + lower_down * 0.4),
+                                vec2(0., start + lower_down * 0.),
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
-                                "Box weight",
+                                "Sideways weight",
                                 TITLE_SIZE,
-                                0.0..1.0,
-                                self.default_settings.box_weight,
-                                current_settings.box_weight,
-                                &mut editing_settings.box_weight,
+                                current_settings.slider_range("box_weight_x", 0.0..1.0),
+                                3,
+                                self.default_settings.box_weight_x,
+                                current_settings.box_weight_x,
+                                &mut editing_settings.box_weight_x,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Up/down weight",
+                                TITLE_SIZE,
+                                current_settings.slider_range("box_weight_y", 0.0..1.0),
+                                3,
+                                self.default_settings.box_weight_y,
+                                current_settings.box_weight_y,
+                                &mut editing_settings.box_weight_y,
                             );
 
                             self.render_toggle(
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., lower_down * -0.05),
+                                vec2(0., lower_down * 0.55),
                                 vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
                                 "Hide weight:",
                                 TOGGLE_TEXT_SIZE,
@@ -424,7 +920,7 @@ impl UiRenderer {
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., lower_down * 1.0),
+                                vec2(0., lower_down * 1.45),
                                 vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
                                 "Quick turn:",
                                 TOGGLE_TEXT_SIZE,
@@ -433,6 +929,9 @@ impl UiRenderer {
                             );
                         }
                         1 => {
+                            // The ball might have been made bigger than the max box size allows.
+                            let min_box_size = MIN_BOX_SIZE.max(editing_settings.min_box_size());
+
                             self.render_slider_uint(
                                 game_assets,
                                 hash!(),
@@ -441,7 +940,7 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Box width",
                                 TITLE_SIZE,
-                                200..1200,
+                                min_box_size..current_settings.max_box_width.max(min_box_size),
                                 self.default_settings.box_width,
                                 current_settings.box_width,
                                 &mut editing_settings.box_width,
@@ -455,7 +954,7 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Box height",
                                 TITLE_SIZE,
-                                200..1200,
+                                min_box_size..current_settings.max_box_height.max(min_box_size),
                                 self.default_settings.box_height,
                                 current_settings.box_height,
                                 &mut editing_settings.box_height,
@@ -469,7 +968,7 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Box thickness",
                                 TITLE_SIZE,
-                                0..100,
+                                0..current_settings.max_box_thickness,
                                 self.default_settings.box_thickness,
                                 current_settings.box_thickness,
                                 &mut editing_settings.box_thickness,
@@ -489,19 +988,82 @@ impl UiRenderer {
                                 &mut editing_settings.box_depth,
                             );
                         }
-                        _ => unreachable!(),
-                    },
-                    SettingsState::Physics(page) => match *page {
-                        0 => {
-                            self.render_slider(
+                        2 => {
+                            self.render_selector(
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., start + lower_down * 0.3),
-                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
-                                "Gravity strength",
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Smoothing:",
+                                TOGGLE_TEXT_SIZE,
+                                &[
+                                    (SmoothingMode::Critical, "Critical"),
+                                    (SmoothingMode::Spring, "Spring"),
+                                    (SmoothingMode::Exponential, "Exponential"),
+                                ],
+                                current_settings.smoothing_mode,
+                                &mut editing_settings.smoothing_mode,
+                            );
+
+                            self.render_slider_uint(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Corner radius",
+                                TITLE_SIZE,
+                                0..300,
+                                self.default_settings.box_corner_radius,
+                                current_settings.box_corner_radius,
+                                &mut editing_settings.box_corner_radius,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Max window speed",
+                                TITLE_SIZE,
+                                current_settings.slider_range("max_window_speed", 0.0..20000.0),
+                                0,
+                                self.default_settings.max_window_speed,
+                                current_settings.max_window_speed,
+                                &mut editing_settings.max_window_speed,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 3.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Recoil strength",
                                 TITLE_SIZE,
-                                -30.0..30.0,
+                                current_settings.slider_range("recoil_strength", 0.0..1.0),
+                                2,
+                                self.default_settings.recoil_strength,
+                                current_settings.recoil_strength,
+                                &mut editing_settings.recoil_strength,
+                            );
+                        }
+                        _ => unreachable!(),
+                    },
+                    SettingsState::Physics(page) => match *page {
+                        0 => {
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.3),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Gravity strength",
+                                TITLE_SIZE,
+                                current_settings.slider_range("gravity_strength", -30.0..30.0),
+                                2,
                                 self.default_settings.gravity_strength,
                                 current_settings.gravity_strength,
                                 &mut editing_settings.gravity_strength,
@@ -515,7 +1077,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Air friction",
                                 TITLE_SIZE,
-                                0.0..1.0,
+                                current_settings.slider_range("air_friction", 0.0..1.0),
+                                2,
                                 self.default_settings.air_friction,
                                 current_settings.air_friction,
                                 &mut editing_settings.air_friction,
@@ -529,7 +1092,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Max velocity",
                                 TITLE_SIZE,
-                                0.0..500.0,
+                                current_settings.slider_range("max_velocity", 0.0..500.0),
+                                0,
                                 self.default_settings.max_velocity,
                                 current_settings.max_velocity,
                                 &mut editing_settings.max_velocity,
@@ -544,7 +1108,8 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Ball bounciness",
                                 TITLE_SIZE,
-                                0.0..1.0,
+                                current_settings.slider_range("ball_bounciness", 0.0..1.0),
+                                2,
                                 self.default_settings.ball_bounciness,
                                 current_settings.ball_bounciness,
                                 &mut editing_settings.ball_bounciness,
@@ -558,166 +1123,773 @@ impl UiRenderer {
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
                                 "Ball weight",
                                 TITLE_SIZE,
-                                0.0..1.0,
+                                current_settings.slider_range("ball_weight", 0.0..1.0),
+                                2,
                                 self.default_settings.ball_weight,
                                 current_settings.ball_weight,
                                 &mut editing_settings.ball_weight,
                             );
 
-                            self.render_slider(
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.7),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Ball friction",
+                                TITLE_SIZE,
+                                current_settings.slider_range("ball_friction", 0.0..1.0),
+                                2,
+                                self.default_settings.ball_friction,
+                                current_settings.ball_friction,
+                                &mut editing_settings.ball_friction,
+                            );
+                        }
+                        2 => {
+                            self.render_selector(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Drag model:",
+                                TOGGLE_TEXT_SIZE,
+                                &[
+                                    (DragModel::Linear, "Linear"),
+                                    (DragModel::Quadratic, "Quadratic"),
+                                ],
+                                current_settings.drag_model,
+                                &mut editing_settings.drag_model,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.2),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Throw strength",
+                                TITLE_SIZE,
+                                current_settings.slider_range("throw_strength", 0.0..5.0),
+                                2,
+                                self.default_settings.throw_strength,
+                                current_settings.throw_strength,
+                                &mut editing_settings.throw_strength,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.2),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Energy decay",
+                                TITLE_SIZE,
+                                current_settings.slider_range("energy_decay", 0.0..1.0),
+                                2,
+                                self.default_settings.energy_decay,
+                                current_settings.energy_decay,
+                                &mut editing_settings.energy_decay,
+                            );
+                        }
+                        3 => {
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.3),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Weight throw effect",
+                                TITLE_SIZE,
+                                current_settings.slider_range("weight_throw_influence", 0.0..1.0),
+                                2,
+                                self.default_settings.weight_throw_influence,
+                                current_settings.weight_throw_influence,
+                                &mut editing_settings.weight_throw_influence,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.5),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Floor friction",
+                                TITLE_SIZE,
+                                current_settings.slider_range("floor_friction_mult", 0.0..2.0),
+                                2,
+                                self.default_settings.floor_friction_mult,
+                                current_settings.floor_friction_mult,
+                                &mut editing_settings.floor_friction_mult,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.7),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Wall friction",
+                                TITLE_SIZE,
+                                current_settings.slider_range("wall_friction_mult", 0.0..2.0),
+                                2,
+                                self.default_settings.wall_friction_mult,
+                                current_settings.wall_friction_mult,
+                                &mut editing_settings.wall_friction_mult,
+                            );
+                        }
+                        4 => {
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.3),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Bounce falloff",
+                                TITLE_SIZE,
+                                current_settings.slider_range("bounce_falloff", 0.0..2.0),
+                                2,
+                                self.default_settings.bounce_falloff,
+                                current_settings.bounce_falloff,
+                                &mut editing_settings.bounce_falloff,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.5),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Velocity filter",
+                                TITLE_SIZE,
+                                current_settings.slider_range("velocity_filter", 0.0..0.2),
+                                3,
+                                self.default_settings.velocity_filter,
+                                current_settings.velocity_filter,
+                                &mut editing_settings.velocity_filter,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.7),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Spin friction",
+                                TITLE_SIZE,
+                                current_settings.slider_range("spin_friction", 0.0..5.0),
+                                2,
+                                self.default_settings.spin_friction,
+                                current_settings.spin_friction,
+                                &mut editing_settings.spin_friction,
+                            );
+                        }
+                        5 => {
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.3),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Gravity gradient",
+                                TITLE_SIZE,
+                                current_settings.slider_range("gravity_gradient", -1.0..1.0),
+                                2,
+                                self.default_settings.gravity_gradient,
+                                current_settings.gravity_gradient,
+                                &mut editing_settings.gravity_gradient,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.5),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Min bounce speed",
+                                TITLE_SIZE,
+                                current_settings.slider_range("min_bounce_speed", 0.0..3000.0),
+                                0,
+                                self.default_settings.min_bounce_speed,
+                                current_settings.min_bounce_speed,
+                                &mut editing_settings.min_bounce_speed,
+                            );
+                        }
+                        6 => {
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Soft collision:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.soft_collision,
+                                &mut editing_settings.soft_collision,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Soft stiffness",
+                                TITLE_SIZE,
+                                current_settings
+                                    .slider_range("soft_collision_stiffness", 100.0..10000.0),
+                                0,
+                                self.default_settings.soft_collision_stiffness,
+                                current_settings.soft_collision_stiffness,
+                                &mut editing_settings.soft_collision_stiffness,
+                            );
+                        }
+                        _ => unreachable!(),
+                    },
+                    SettingsState::FpsDelay(page) => match *page {
+                        0 => {
+                            self.render_slider_uint(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * -0.2),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Delay frames",
+                                TITLE_SIZE,
+                                0..10,
+                                self.default_settings.delay_frames,
+                                current_settings.delay_frames,
+                                &mut editing_settings.delay_frames,
+                            );
+
+                            self.render_maxed_slider_uint(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.8),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Max FPS",
+                                "None".to_string(),
+                                TITLE_SIZE,
+                                10..FPS_LIMIT,
+                                self.default_settings.max_fps,
+                                current_settings.max_fps,
+                                &mut editing_settings.max_fps,
+                            );
+
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.6),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "VSync:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.vsync,
+                                &mut editing_settings.vsync,
+                            );
+
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 0.9),
+                                "Note: When using VSync, it will",
+                                16,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 1.2),
+                                "automatically cap the FPS.",
+                                16,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 1.5),
+                                "If you're using VSync, consider",
+                                16,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 1.8),
+                                "setting Max FPS to the max.",
+                                16,
+                            );
+                        }
+                        1 => {
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Fixed timestep:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.fixed_timestep,
+                                &mut editing_settings.fixed_timestep,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 0.3),
+                                "Makes the physics act the same",
+                                16,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 0.6),
+                                "no matter the FPS.",
+                                16,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.2),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Max frame time",
+                                TITLE_SIZE,
+                                current_settings
+                                    .slider_range("max_delta_time", MIN_MAX_DELTA_TIME..0.5),
+                                3,
+                                self.default_settings.max_delta_time,
+                                current_settings.max_delta_time,
+                                &mut editing_settings.max_delta_time,
+                            );
+                        }
+                        _ => unreachable!(),
+                    },
+                    SettingsState::Misc(page) => match *page {
+                        0 => {
+                            self.render_slider_uint(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * -0.2),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Ball radius",
+                                TITLE_SIZE,
+                                1..400,
+                                self.default_settings.ball_radius,
+                                current_settings.ball_radius,
+                                &mut editing_settings.ball_radius,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.8),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Game speed",
+                                TITLE_SIZE,
+                                current_settings.slider_range("speed_mul", 0.1..3.0),
+                                2,
+                                self.default_settings.speed_mul,
+                                current_settings.speed_mul,
+                                &mut editing_settings.speed_mul,
+                            );
+
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.6),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Click to drag:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.click_to_drag,
+                                &mut editing_settings.click_to_drag,
+                            );
+
+                            self.render_text(
+                                game_assets,
+                                vec2(0., start + lower_down * 2.45),
+                                &format!("Current ball: {}", editing_settings.last_ball),
+                                18,
+                            );
+
+                            self.render_text(
+                                game_assets,
+                                vec2(0., start + lower_down * 2.8),
+                                &format!("Current sounds: {}", editing_settings.last_sounds),
+                                18,
+                            );
+
+                            self.render_text(
+                                game_assets,
+                                vec2(0., start + lower_down * 3.15),
+                                &format!(
+                                    "Current asset pack: {}",
+                                    if editing_settings.last_asset_pack.is_empty() {
+                                        "None"
+                                    } else {
+                                        &editing_settings.last_asset_pack
+                                    }
+                                ),
+                                18,
+                            );
+                        }
+                        1 => {
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Spawn at cursor:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.spawn_at_cursor,
+                                &mut editing_settings.spawn_at_cursor,
+                            );
+
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.9),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Idle throttle:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.idle_throttle,
+                                &mut editing_settings.idle_throttle,
+                            );
+
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.8),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Free roam:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.free_roam,
+                                &mut editing_settings.free_roam,
+                            );
+
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.7),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Grab ball:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.grab_ball,
+                                &mut editing_settings.grab_ball,
+                            );
+                        }
+                        2 => {
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Ball rotation",
+                                TITLE_SIZE,
+                                current_settings.slider_range("ball_start_rotation", 0.0..360.0),
+                                0,
+                                self.default_settings.ball_start_rotation,
+                                current_settings.ball_start_rotation,
+                                &mut editing_settings.ball_start_rotation,
+                            );
+
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Flip with direction:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.flip_with_direction,
+                                &mut editing_settings.flip_with_direction,
+                            );
+
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.9),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Fullscreen:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.fullscreen,
+                                &mut editing_settings.fullscreen,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.9),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Slowdown speed",
+                                TITLE_SIZE,
+                                current_settings.slider_range("slowdown_factor", 0.01..1.0),
+                                2,
+                                self.default_settings.slowdown_factor,
+                                current_settings.slowdown_factor,
+                                &mut editing_settings.slowdown_factor,
+                            );
+                        }
+                        3 => {
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Cursor gravity:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.cursor_gravity,
+                                &mut editing_settings.cursor_gravity,
+                            );
+
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Cursor pull",
+                                TITLE_SIZE,
+                                current_settings.slider_range("cursor_gravity_strength", 0.0..30.0),
+                                2,
+                                self.default_settings.cursor_gravity_strength,
+                                current_settings.cursor_gravity_strength,
+                                &mut editing_settings.cursor_gravity_strength,
+                            );
+
+                            self.render_selector(
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., start + lower_down * 2.7),
-                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
-                                "Ball friction",
-                                TITLE_SIZE,
-                                0.0..1.0,
-                                self.default_settings.ball_friction,
-                                current_settings.ball_friction,
-                                &mut editing_settings.ball_friction,
+                                vec2(0., start + lower_down * 2.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Name match:",
+                                TOGGLE_TEXT_SIZE,
+                                &[
+                                    (MatchMode::Suffix, "Suffix"),
+                                    (MatchMode::Exact, "Exact"),
+                                    (MatchMode::Contains, "Contains"),
+                                ],
+                                current_settings.match_mode,
+                                &mut editing_settings.match_mode,
                             );
-                        }
-                        _ => unreachable!(),
-                    },
-                    SettingsState::FpsDelay(page) => match *page {
-                        0 => {
-                            self.render_slider_uint(
+
+                            self.render_slider(
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., start + lower_down * -0.2),
+                                vec2(0., start + lower_down * 3.),
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
-                                "Delay frames",
+                                "Idle bob",
                                 TITLE_SIZE,
-                                0..10,
-                                self.default_settings.delay_frames,
-                                current_settings.delay_frames,
-                                &mut editing_settings.delay_frames,
+                                current_settings.slider_range("idle_bob", 0.0..20.0),
+                                1,
+                                self.default_settings.idle_bob,
+                                current_settings.idle_bob,
+                                &mut editing_settings.idle_bob,
                             );
-
-                            self.render_maxed_slider_uint(
+                        }
+                        4 => {
+                            self.render_slider_uint(
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., start + lower_down * 0.8),
+                                vec2(0., start + lower_down * 0.),
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
-                                "Max FPS",
-                                "None".to_string(),
+                                "Errors shown",
                                 TITLE_SIZE,
-                                10..FPS_LIMIT,
-                                self.default_settings.max_fps,
-                                current_settings.max_fps,
-                                &mut editing_settings.max_fps,
+                                1..ERROR_MAX_COUNT as u32,
+                                self.default_settings.max_visible_errors,
+                                current_settings.max_visible_errors,
+                                &mut editing_settings.max_visible_errors,
                             );
 
                             self.render_toggle(
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., start + lower_down * 1.6),
+                                vec2(0., start + lower_down * 1.),
                                 vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
-                                "VSync:",
+                                "Click-through:",
                                 TOGGLE_TEXT_SIZE,
-                                current_settings.vsync,
-                                &mut editing_settings.vsync,
-                            );
-
-                            self.render_text(
-                                game_assets,
-                                vec2(0., 0. + lower_down * 0.9),
-                                "Note: When using VSync, it will",
-                                16,
+                                current_settings.clickthrough_background,
+                                &mut editing_settings.clickthrough_background,
                             );
                             self.render_text(
                                 game_assets,
-                                vec2(0., 0. + lower_down * 1.2),
-                                "automatically cap the FPS.",
+                                vec2(0., 0. + lower_down * 0.5),
+                                "Clicks outside the rounded box go",
                                 16,
                             );
                             self.render_text(
                                 game_assets,
-                                vec2(0., 0. + lower_down * 1.5),
-                                "If you're using VSync, consider",
+                                vec2(0., 0. + lower_down * 0.8),
+                                "to the windows behind it, so set a",
                                 16,
                             );
                             self.render_text(
                                 game_assets,
-                                vec2(0., 0. + lower_down * 1.8),
-                                "setting Max FPS to the max.",
+                                vec2(0., 0. + lower_down * 1.1),
+                                "corner radius. (Windows only)",
                                 16,
                             );
                         }
-                        _ => unreachable!(),
-                    },
-                    SettingsState::Misc(page) => match *page {
-                        0 => {
-                            self.render_slider_uint(
+                        5 => {
+                            self.render_toggle(
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., start + lower_down * -0.2),
-                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
-                                "Ball radius",
-                                TITLE_SIZE,
-                                1..400,
-                                self.default_settings.ball_radius,
-                                current_settings.ball_radius,
-                                &mut editing_settings.ball_radius,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Attract mode:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.attract_mode,
+                                &mut editing_settings.attract_mode,
                             );
 
                             self.render_slider(
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., start + lower_down * 0.8),
+                                vec2(0., start + lower_down * 1.),
                                 vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
-                                "Game speed",
+                                "Attract delay",
                                 TITLE_SIZE,
-                                0.1..3.0,
-                                self.default_settings.speed_mul,
-                                current_settings.speed_mul,
-                                &mut editing_settings.speed_mul,
+                                current_settings.slider_range("attract_delay", 1.0..300.0),
+                                0,
+                                self.default_settings.attract_delay,
+                                current_settings.attract_delay,
+                                &mut editing_settings.attract_delay,
                             );
 
-                            self.render_toggle(
+                            self.render_selector(
                                 game_assets,
                                 hash!(),
                                 mouse_pos,
-                                vec2(0., start + lower_down * 1.6),
+                                vec2(0., start + lower_down * 2.),
                                 vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
-                                "Click to drag:",
+                                "Magnet wall:",
                                 TOGGLE_TEXT_SIZE,
-                                current_settings.click_to_drag,
-                                &mut editing_settings.click_to_drag,
+                                &[
+                                    (MagnetWall::Off, "Off"),
+                                    (MagnetWall::Floor, "Floor"),
+                                    (MagnetWall::Ceiling, "Ceiling"),
+                                    (MagnetWall::Right, "Right"),
+                                    (MagnetWall::Left, "Left"),
+                                ],
+                                current_settings.magnet_wall,
+                                &mut editing_settings.magnet_wall,
                             );
 
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 3.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Lock window:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.lock_window,
+                                &mut editing_settings.lock_window,
+                            );
+                        }
+                        6 => {
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Hide cursor after",
+                                TITLE_SIZE,
+                                current_settings.slider_range("cursor_hide_delay", 0.0..30.0),
+                                1,
+                                self.default_settings.cursor_hide_delay,
+                                current_settings.cursor_hide_delay,
+                                &mut editing_settings.cursor_hide_delay,
+                            );
                             self.render_text(
                                 game_assets,
-                                vec2(0., start + lower_down * 2.45),
-                                &format!("Current ball: {}", editing_settings.last_ball),
-                                18,
+                                vec2(0., 0. + lower_down * 0.3),
+                                "Seconds without moving the mouse",
+                                16,
                             );
-
                             self.render_text(
                                 game_assets,
-                                vec2(0., start + lower_down * 2.8),
-                                &format!("Current sounds: {}", editing_settings.last_sounds),
-                                18,
+                                vec2(0., 0. + lower_down * 0.6),
+                                "before the cursor hides. 0 is off.",
+                                16,
                             );
 
+                            self.render_slider(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 2.6),
+                                vec2(SLIDER_WIDTH, SLIDER_HEIGHT),
+                                "Error display time",
+                                TITLE_SIZE,
+                                current_settings.slider_range("error_display_time", 0.0..60.0),
+                                1,
+                                self.default_settings.error_display_time,
+                                current_settings.error_display_time,
+                                &mut editing_settings.error_display_time,
+                            );
+                        }
+                        7 => {
+                            if self.render_button(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.2),
+                                BUTTON_SIZE * vec2(1.05, 0.8),
+                                "Copy settings",
+                                DEFAULT_TEXT_COLOR,
+                                20,
+                            ) {
+                                self.copy_settings = true;
+                            }
+
+                            if self.render_button(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 1.4),
+                                BUTTON_SIZE * vec2(1.05, 0.8),
+                                "Paste settings",
+                                DEFAULT_TEXT_COLOR,
+                                20,
+                            ) {
+                                self.paste_settings = true;
+                            }
+
                             self.render_text(
                                 game_assets,
-                                vec2(0., start + lower_down * 3.15),
-                                &format!(
-                                    "Current asset pack: {}",
-                                    if editing_settings.last_asset_pack.is_empty() {
-                                        "None"
-                                    } else {
-                                        &editing_settings.last_asset_pack
-                                    }
-                                ),
-                                18,
+                                vec2(0., 0. + lower_down * 1.1),
+                                "Pasted settings still",
+                                16,
+                            );
+                            self.render_text(
+                                game_assets,
+                                vec2(0., 0. + lower_down * 1.4),
+                                "need to be applied.",
+                                16,
+                            );
+                        }
+                        8 => {
+                            self.render_toggle(
+                                game_assets,
+                                hash!(),
+                                mouse_pos,
+                                vec2(0., start + lower_down * 0.),
+                                vec2(TOGGLE_WIDTH, TOGGLE_HEIGHT),
+                                "Open menu on start:",
+                                TOGGLE_TEXT_SIZE,
+                                current_settings.open_menu_on_start,
+                                &mut editing_settings.open_menu_on_start,
                             );
                         }
                         _ => unreachable!(),
@@ -817,27 +1989,79 @@ impl UiRenderer {
                 ) {
                     let understands_moving = editing_settings.understands_moving;
                     let understands_menu = editing_settings.understands_menu;
+                    // The ranges can only be changed in the settings file, so they shouldn't get lost here.
+                    let ranges = std::mem::take(&mut editing_settings.ranges);
 
                     *editing_settings = self.default_settings.clone();
 
                     editing_settings.understands_moving = understands_moving;
                     editing_settings.understands_menu = understands_menu;
+                    editing_settings.ranges = ranges;
                 }
             }
 
-            let center_offset_x = -MENU_SIZE.x / 2. + BUTTON_SIZE.x / 2. + BUTTONS_MARGIN / 2.;
-
             let y_offset = -MENU_SIZE.y / 2.
                 + MENU_PADDING
                 + BUTTONS_MARGIN
                 + BUTTON_SIZE.y / SMALL_BUTTON_DIV / 2.;
 
+            // Pages get a third button in the middle to only reset the settings on that page, so the buttons have to be thinner.
+            let is_page = settings_state.get_page_info_mut().is_some();
+
+            let (bottom_button_size, center_offset_x) = if is_page {
+                const BOTTOM_BUTTON_SPACING: f32 = 6.;
+                let bottom_button_size =
+                    vec2(BUTTON_SIZE.x / 1.8, BUTTON_SIZE.y / SMALL_BUTTON_DIV);
+                (
+                    bottom_button_size,
+                    -bottom_button_size.x - BOTTOM_BUTTON_SPACING,
+                )
+            } else {
+                (
+                    BUTTON_SIZE / SMALL_BUTTON_DIV,
+                    -MENU_SIZE.x / 2. + BUTTON_SIZE.x / 2. + BUTTONS_MARGIN / 2.,
+                )
+            };
+
+            if is_page {
+                if self.render_button(
+                    game_assets,
+                    hash!(),
+                    mouse_pos,
+                    vec2(0., -y_offset),
+                    bottom_button_size,
+                    "Reset",
+                    DARKRED_TEXT_COLOR,
+                    24,
+                ) {
+                    match settings_state {
+                        SettingsState::Audio(_) => {
+                            editing_settings.reset_audio(&self.default_settings)
+                        }
+                        SettingsState::Visuals(_) => {
+                            editing_settings.reset_visual(&self.default_settings)
+                        }
+                        SettingsState::Box(_) => editing_settings.reset_box(&self.default_settings),
+                        SettingsState::Physics(_) => {
+                            editing_settings.reset_physics(&self.default_settings)
+                        }
+                        SettingsState::FpsDelay(_) => {
+                            editing_settings.reset_fps_delay(&self.default_settings)
+                        }
+                        SettingsState::Misc(_) => {
+                            editing_settings.reset_misc(&self.default_settings)
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+            }
+
             if self.render_button(
                 game_assets,
                 hash!(),
                 mouse_pos,
                 vec2(center_offset_x, -y_offset),
-                BUTTON_SIZE / SMALL_BUTTON_DIV,
+                bottom_button_size,
                 "Back",
                 DEFAULT_TEXT_COLOR,
                 28,
@@ -845,14 +2069,29 @@ impl UiRenderer {
                 settings_state.back();
             }
 
-            if self.render_button(
+            let settings_changed = current_settings != editing_settings;
+
+            if self.can_undo && !settings_changed {
+                if self.render_button(
+                    game_assets,
+                    hash!(),
+                    mouse_pos,
+                    vec2(-center_offset_x, -y_offset),
+                    bottom_button_size,
+                    "Undo",
+                    DEFAULT_TEXT_COLOR,
+                    28,
+                ) {
+                    self.undo = true;
+                }
+            } else if self.render_button(
                 game_assets,
                 hash!(),
                 mouse_pos,
                 vec2(-center_offset_x, -y_offset),
-                BUTTON_SIZE / SMALL_BUTTON_DIV,
+                bottom_button_size,
                 "Apply",
-                get_changed_default_color(current_settings != editing_settings),
+                get_changed_default_color(settings_changed),
                 28,
             ) {
                 save = true;
@@ -912,6 +2151,7 @@ impl UiRenderer {
         text: &str,
         font_size: u16,
     ) {
+        let text = game_assets.translations.get(text);
         let rect = Rect::new(
             (center_pos.x * 2.) * self.mult,
             (center_pos.y * 2.) * self.mult,
@@ -919,7 +2159,7 @@ impl UiRenderer {
             0.,
         );
 
-        let size = measure_text(text, game_assets.font.as_ref(), font_size, 2.0 * self.mult);
+        let size = measure_text(text, Some(&game_assets.font), font_size, 2.0 * self.mult);
 
         draw_text_ex(
             text,
@@ -927,7 +2167,7 @@ impl UiRenderer {
             rect.y + font_size as f32 / 2. * self.mult,
             TextParams {
                 color: DEFAULT_TEXT_COLOR,
-                font: game_assets.font.as_ref(),
+                font: Some(&game_assets.font),
                 font_size,
                 font_scale: 2.0 * self.mult,
                 ..Default::default()
@@ -946,6 +2186,7 @@ impl UiRenderer {
         text_color: Color,
         font_size: u16,
     ) -> bool {
+        let text = game_assets.translations.get(text);
         let rect = Rect::new(
             (center_pos.x * 2. - size.x) * self.mult,
             (center_pos.y * 2. - size.y) * self.mult,
@@ -958,6 +2199,12 @@ impl UiRenderer {
         let mouse_is_pressed = is_mouse_button_pressed(MouseButton::Left);
         let mouse_is_down = is_mouse_button_down(MouseButton::Left) || mouse_is_released;
 
+        let is_focused = self.next_focusable();
+        let focus_activated = is_focused && self.focus_activated;
+        if focus_activated {
+            self.focus_activated = false;
+        }
+
         if contains_mouse {
             set_mouse_cursor(CursorIcon::Pointer);
             if mouse_is_pressed {
@@ -972,7 +2219,7 @@ impl UiRenderer {
 
         let color = if button_is_active && mouse_is_down {
             Color::new(0.80, 0.80, 0.80, 1.0)
-        } else if contains_mouse {
+        } else if contains_mouse || is_focused {
             Color::new(0.90, 0.90, 0.90, 1.0)
         } else {
             WHITE
@@ -989,7 +2236,7 @@ impl UiRenderer {
             },
         );
 
-        let size = measure_text(text, game_assets.font.as_ref(), font_size, 2.0 * self.mult);
+        let size = measure_text(text, Some(&game_assets.font), font_size, 2.0 * self.mult);
 
         draw_text_ex(
             text,
@@ -997,16 +2244,19 @@ impl UiRenderer {
             rect.y + rect.h / 2. + font_size as f32 / 2. * self.mult,
             TextParams {
                 color: text_color,
-                font: game_assets.font.as_ref(),
+                font: Some(&game_assets.font),
                 font_size,
                 font_scale: 2.0 * self.mult,
                 ..Default::default()
             },
         );
 
-        self.interacted = self.interacted || button_is_active && mouse_is_released;
+        let clicked = button_is_active && mouse_is_released || focus_activated;
 
-        return button_is_active && mouse_is_released;
+        self.interacted = self.interacted || clicked;
+        self.clicked_button = self.clicked_button || clicked;
+
+        return clicked;
     }
 
     pub fn render_toggle(
@@ -1021,6 +2271,7 @@ impl UiRenderer {
         prev_value: bool,
         value: &mut bool,
     ) {
+        let text = game_assets.translations.get(text);
         let rect = Rect::new(
             (center_pos.x * 2. - size.x) * self.mult,
             (center_pos.y * 2. - size.y) * self.mult,
@@ -1052,7 +2303,66 @@ impl UiRenderer {
             rect.y + rect.h / 2. + font_size as f32 / 2. * self.mult,
             TextParams {
                 color: DEFAULT_TEXT_COLOR,
-                font: game_assets.font.as_ref(),
+                font: Some(&game_assets.font),
+                font_size,
+                font_scale: 2.0 * self.mult,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Like a toggle, but the button cycles through a list of options.
+    pub fn render_selector<T: PartialEq + Copy>(
+        &mut self,
+        game_assets: &GameAssets,
+        id: u64,
+        mouse_pos: Vec2,
+        center_pos: Vec2,
+        size: Vec2,
+        text: &str,
+        font_size: u16,
+        options: &[(T, &str)],
+        prev_value: T,
+        value: &mut T,
+    ) {
+        let text = game_assets.translations.get(text);
+        let rect = Rect::new(
+            (center_pos.x * 2. - size.x) * self.mult,
+            (center_pos.y * 2. - size.y) * self.mult,
+            size.x * 2. * self.mult,
+            size.y * 2. * self.mult,
+        );
+
+        const BUTTON_RATIO: f32 = 2.5;
+
+        let button_size = vec2(size.y * BUTTON_RATIO, size.y);
+        let button_center_pos = center_pos + vec2(size.x - button_size.x, 0.0) / 2.;
+
+        let current_index = options
+            .iter()
+            .position(|(option, _)| *option == *value)
+            .unwrap_or(0);
+
+        if self.render_button(
+            game_assets,
+            id,
+            mouse_pos,
+            button_center_pos,
+            button_size,
+            options[current_index].1,
+            get_changed_color(*value != prev_value),
+            (button_size.y * 0.45) as u16,
+        ) {
+            *value = options[(current_index + 1) % options.len()].0;
+        }
+
+        draw_text_ex(
+            text,
+            rect.x,
+            rect.y + rect.h / 2. + font_size as f32 / 2. * self.mult,
+            TextParams {
+                color: DEFAULT_TEXT_COLOR,
+                font: Some(&game_assets.font),
                 font_size,
                 font_scale: 2.0 * self.mult,
                 ..Default::default()
@@ -1071,11 +2381,14 @@ impl UiRenderer {
         font_size: u16,
         start: f32,
         end: f32,
+        step: f32,
+        scroll_step: f32,
         default_value: f32,
         prev_value: f32,
         value: &mut f32,
         get_value_processed: T,
     ) {
+        let title = game_assets.translations.get(title);
         let slider_size = 0.85;
 
         let full_rect = Rect::new(
@@ -1103,6 +2416,8 @@ impl UiRenderer {
         let mouse_is_pressed = is_mouse_button_pressed(MouseButton::Left);
         let mouse_is_down = is_mouse_button_down(MouseButton::Left);
 
+        let is_focused = self.next_focusable();
+
         if contains_mouse {
             set_mouse_cursor(CursorIcon::Pointer);
         }
@@ -1121,11 +2436,47 @@ impl UiRenderer {
         } else if is_key_pressed(KeyCode::Enter) && self.active_id == id {
             self.active_id = 0;
             self.user_input = String::new()
+        } else if is_focused && self.focus_activated {
+            // Lets the user type in a value without using the mouse.
+            self.focus_activated = false;
+            self.active_id = id;
+            self.slider_follow = false;
+            self.user_input = String::new();
+            self.interacted = true;
+        }
+
+        if is_focused && self.focus_adjust != 0. {
+            *value = (*value + step * self.focus_adjust).clamp(start, end);
+            self.user_input = String::new();
+        }
+
+        let (_, wheel_y) = mouse_wheel();
+        if contains_mouse && wheel_y != 0. && !mouse_is_down {
+            let scroll_step = if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift)
+            {
+                scroll_step * BIG_SCROLL_STEP_MULT
+            } else {
+                scroll_step
+            };
+            *value = (*value + scroll_step * wheel_y.signum()).clamp(start, end);
+            self.user_input = String::new();
+            self.interacted = true;
+        }
+
+        // Counts as interacting, so a quick second right click doesn't close the menu.
+        if contains_mouse && is_mouse_button_pressed(MouseButton::Right) {
+            *value = default_value;
+            self.user_input = String::new();
+            self.interacted = true;
         }
 
         let is_active = self.active_id == id;
         let will_follow = is_active && mouse_is_down && self.slider_follow;
 
+        if mouse_is_pressed || !mouse_is_down {
+            self.fine_slider_anchor = None;
+        }
+
         let bar_width_pct = 0.1;
         let bar_height_pct = 1.25;
         let bar_width = slider_rect.w * bar_width_pct;
@@ -1134,11 +2485,14 @@ impl UiRenderer {
         let value_string;
 
         if will_follow {
-            let amount = ((mouse_pos.x - slider_rect.x - bar_width / 2.)
-                / (slider_rect.w - bar_width))
-                .clamp(0., 1.);
-            let ranged_amount = start + amount * (end - start);
-            *value = ranged_amount;
+            *value = self.get_followed_slider_value(
+                mouse_pos.x,
+                slider_rect,
+                bar_width,
+                start,
+                end,
+                *value,
+            );
             self.user_input = String::new();
             (*value, value_string) = get_value_processed(*value);
         } else if is_active && !self.user_input.is_empty() {
@@ -1196,7 +2550,7 @@ impl UiRenderer {
 
         let size = measure_text(
             &value_string,
-            game_assets.font.as_ref(),
+            Some(&game_assets.font),
             value_font_size,
             2.0 * self.mult,
         );
@@ -1213,7 +2567,7 @@ impl UiRenderer {
                 } else {
                     BLACK
                 },
-                font: game_assets.font.as_ref(),
+                font: Some(&game_assets.font),
                 font_size: value_font_size,
                 font_scale: 2.0 * self.mult,
                 ..Default::default()
@@ -1225,8 +2579,12 @@ impl UiRenderer {
             full_rect.x,
             full_rect.y - font_size as f32 * 0.65 * self.mult,
             TextParams {
-                color: DEFAULT_TEXT_COLOR,
-                font: game_assets.font.as_ref(),
+                color: if is_focused {
+                    ACTIVE_TEXT_COLOR
+                } else {
+                    DEFAULT_TEXT_COLOR
+                },
+                font: Some(&game_assets.font),
                 font_size,
                 font_scale: 2.0 * self.mult,
                 ..Default::default()
@@ -1234,6 +2592,32 @@ impl UiRenderer {
         );
     }
 
+    /// Gets the value of a slider that is following the mouse.
+    ///
+    /// Holding shift moves the value slower than the mouse, starting from where the value was when shift got pressed.
+    fn get_followed_slider_value(
+        &mut self,
+        mouse_x: f32,
+        slider_rect: Rect,
+        bar_width: f32,
+        start: f32,
+        end: f32,
+        value: f32,
+    ) -> f32 {
+        let movable_width = slider_rect.w - bar_width;
+
+        if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+            let (anchor_mouse_x, anchor_value) =
+                *self.fine_slider_anchor.get_or_insert((mouse_x, value));
+            let amount = (mouse_x - anchor_mouse_x) / movable_width * FINE_SLIDER_SCALE;
+            return (anchor_value + amount * (end - start)).clamp(start, end);
+        }
+
+        self.fine_slider_anchor = None;
+        let amount = ((mouse_x - slider_rect.x - bar_width / 2.) / movable_width).clamp(0., 1.);
+        return start + amount * (end - start);
+    }
+
     pub fn render_slider(
         &mut self,
         game_assets: &GameAssets,
@@ -1244,6 +2628,7 @@ impl UiRenderer {
         title: &str,
         font_size: u16,
         range: Range<f32>,
+        precision: usize,
         default_value: f32,
         prev_value: f32,
         value: &mut f32,
@@ -1258,10 +2643,12 @@ impl UiRenderer {
             font_size,
             range.start,
             range.end,
+            (range.end - range.start) / 100.,
+            (range.end - range.start) / 100.,
             default_value,
             prev_value,
             value,
-            |value| return (value, format!("{:.2}", value)),
+            |value| return (value, format!("{:.*}", precision, value)),
         );
     }
 
@@ -1280,6 +2667,7 @@ impl UiRenderer {
         value: &mut u32,
     ) {
         let mut float_value = *value as f32;
+        let start = range.start.min(range.end);
         self.render_slider_common(
             game_assets,
             id,
@@ -1288,8 +2676,10 @@ impl UiRenderer {
             size,
             title,
             font_size,
-            range.start as f32,
+            start as f32,
             range.end as f32,
+            ((range.end - start) as f32 / 100.).round().max(1.),
+            1.,
             default_value as f32,
             prev_value as f32,
             &mut float_value,
@@ -1328,6 +2718,8 @@ impl UiRenderer {
             font_size,
             range.start as f32,
             range.end as f32,
+            ((range.end - range.start) as f32 / 100.).round().max(1.),
+            1.,
             default_value as f32,
             prev_value as f32,
             &mut float_value,
@@ -0,0 +1,148 @@
+use macroquad::math::Vec2;
+
+/// If the window can be made click-through on this platform.
+pub const CLICKTHROUGH_SUPPORTED: bool = cfg!(target_os = "windows");
+
+/// Checks if a position is outside of a box with rounded corners.
+///
+/// `position` is from the center of the box, and `half_size` is half of the size of the box.
+pub fn is_outside_rounded_box(position: Vec2, half_size: Vec2, corner_radius: f32) -> bool {
+    let corner_radius = corner_radius.min(half_size.min_element()).max(0.);
+    // How far past the center of the nearest corner the position is.
+    let from_corner_center = position.abs() - (half_size - corner_radius);
+
+    if from_corner_center.x > corner_radius || from_corner_center.y > corner_radius {
+        return true;
+    }
+
+    return from_corner_center.x > 0.
+        && from_corner_center.y > 0.
+        && from_corner_center.length() > corner_radius;
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use std::ffi::c_void;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn EnumThreadWindows(
+            dw_thread_id: u32,
+            lpfn: extern "system" fn(*mut c_void, isize) -> i32,
+            l_param: isize,
+        ) -> i32;
+        fn IsWindowVisible(hwnd: *mut c_void) -> i32;
+        pub fn GetWindowLongW(hwnd: *mut c_void, n_index: i32) -> i32;
+        pub fn SetWindowLongW(hwnd: *mut c_void, n_index: i32, dw_new_long: i32) -> i32;
+        pub fn SetLayeredWindowAttributes(
+            hwnd: *mut c_void,
+            cr_key: u32,
+            b_alpha: u8,
+            dw_flags: u32,
+        ) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentThreadId() -> u32;
+    }
+
+    extern "system" fn window_enum_proc(hwnd: *mut c_void, l_param: isize) -> i32 {
+        if unsafe { IsWindowVisible(hwnd) } == 0 {
+            return 1;
+        }
+
+        let found_hwnd = unsafe { &mut *(l_param as *mut *mut c_void) };
+        *found_hwnd = hwnd;
+
+        // Stop enumerating.
+        return 0;
+    }
+
+    /// Finds the window of the game. It only has one, and it's created on the main thread.
+    pub fn find_game_window() -> Option<*mut c_void> {
+        let mut hwnd: *mut c_void = std::ptr::null_mut();
+        unsafe {
+            EnumThreadWindows(
+                GetCurrentThreadId(),
+                window_enum_proc,
+                &mut hwnd as *mut *mut c_void as isize,
+            );
+        }
+
+        if hwnd.is_null() {
+            return None;
+        }
+
+        return Some(hwnd);
+    }
+}
+
+/// Makes clicks on the window go to the windows behind it, or makes the window take clicks again.
+///
+/// The window doesn't get any mouse input while it's click-through.
+/// Does nothing where `CLICKTHROUGH_SUPPORTED` is false.
+#[cfg(target_os = "windows")]
+pub fn set_window_clickthrough(clickthrough: bool) {
+    use windows::*;
+
+    const GWL_EXSTYLE: i32 = -20;
+    const WS_EX_LAYERED: i32 = 0x0008_0000;
+    const WS_EX_TRANSPARENT: i32 = 0x0000_0020;
+    const LWA_ALPHA: u32 = 0x0000_0002;
+
+    let Some(hwnd) = find_game_window() else {
+        return;
+    };
+
+    let ex_style = unsafe { GetWindowLongW(hwnd, GWL_EXSTYLE) };
+
+    if clickthrough {
+        // Clicks only go through windows that are layered.
+        unsafe {
+            SetWindowLongW(
+                hwnd,
+                GWL_EXSTYLE,
+                ex_style | WS_EX_LAYERED | WS_EX_TRANSPARENT,
+            )
+        };
+        if ex_style & WS_EX_LAYERED == 0 {
+            // Fully opaque, so it looks the same as before.
+            unsafe { SetLayeredWindowAttributes(hwnd, 0, 255, LWA_ALPHA) };
+        }
+    } else if ex_style & WS_EX_TRANSPARENT != 0 {
+        unsafe { SetWindowLongW(hwnd, GWL_EXSTYLE, ex_style & !WS_EX_TRANSPARENT) };
+    }
+}
+
+/// Makes clicks on the window go to the windows behind it, or makes the window take clicks again.
+///
+/// The window doesn't get any mouse input while it's click-through.
+/// Does nothing where `CLICKTHROUGH_SUPPORTED` is false.
+#[cfg(not(target_os = "windows"))]
+pub fn set_window_clickthrough(_clickthrough: bool) {}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::math::vec2;
+
+    use super::*;
+
+    #[test]
+    fn only_corners_are_outside_rounded_box() {
+        let half_size = vec2(200., 100.);
+
+        assert!(!is_outside_rounded_box(Vec2::ZERO, half_size, 50.));
+        // On the straight edges.
+        assert!(!is_outside_rounded_box(vec2(199., 0.), half_size, 50.));
+        assert!(!is_outside_rounded_box(vec2(0., -99.), half_size, 50.));
+        // In the corner of the rectangle, but outside of the rounded corner.
+        assert!(is_outside_rounded_box(vec2(199., 99.), half_size, 50.));
+        assert!(is_outside_rounded_box(vec2(-199., -99.), half_size, 50.));
+        // Inside the rounded corner.
+        assert!(!is_outside_rounded_box(vec2(180., 80.), half_size, 50.));
+        // Sharp corners have nothing outside.
+        assert!(!is_outside_rounded_box(vec2(199., 99.), half_size, 0.));
+        assert!(is_outside_rounded_box(vec2(201., 0.), half_size, 0.));
+    }
+}